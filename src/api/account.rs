@@ -1,6 +1,8 @@
-use crate::{Address, types::BlockHash, units::Raw};
+use crate::{Address, BananoApi, Error, encoding::{deserialize_bool, deserialize_empty_as_default}, types::{BlockHash, BlockSubtype, BlockType, Link}, units::Raw};
+use super::cache::Caching;
 use chrono::{DateTime, Utc};
 use serde::Deserialize;
+use serde_json::{json, Value};
 use serde_with::serde_as;
 
 type BlockCount = u128;
@@ -51,3 +53,137 @@ pub struct AccountInfo {
 	/// Only available for version 21.0+ which is the block hash at that confirmation height
 	pub confirmation_height_frontier: Option<String>,
 }
+
+/// Options for [account_history](crate::BananoApi::account_history)
+///
+/// [Nano documentation](https://docs.nano.org/commands/rpc-protocol/#account_history)
+#[derive(Debug, Clone, Default)]
+pub struct AccountHistoryOptions {
+    /// Return every block field, including change blocks
+    pub raw: bool,
+    /// Start from this block instead of the frontier
+    pub head: Option<BlockHash>,
+    /// Skip this many blocks
+    pub offset: Option<u64>,
+    /// Walk the chain from the open block (or `head`) forwards
+    pub reverse: bool,
+    /// Only return blocks sending to or receiving from these accounts
+    pub account_filter: Vec<Address>,
+}
+
+/// Block of an account history
+#[serde_as]
+#[derive(Debug, Clone, Deserialize)]
+pub struct HistoryEntry {
+    /// `send` or `receive`, or the actual block type in raw mode
+    #[serde(rename = "type")]
+    pub kind: BlockType,
+    /// Only available in raw mode
+    pub subtype: Option<BlockSubtype>,
+    /// Destination of a send or source of a receive
+    pub account: Option<Address>,
+    pub amount: Option<Raw>,
+    #[serde_as(as = "serde_with::TimestampSeconds<String>")]
+    pub local_timestamp: DateTime<Utc>,
+    #[serde(with = "serde_with::rust::display_fromstr")]
+    pub height: u64,
+    pub hash: BlockHash,
+    /// Nodes before V23 do not tell, in which case this is `false`
+    #[serde(default, deserialize_with = "deserialize_bool")]
+    pub confirmed: bool,
+    /// Only available in raw mode
+    pub representative: Option<Address>,
+    /// Only available in raw mode
+    pub balance: Option<Raw>,
+    /// Only available in raw mode
+    pub previous: Option<BlockHash>,
+    /// Only available in raw mode
+    pub link: Option<Link>,
+}
+
+/// Account history
+///
+/// [Nano documentation](https://docs.nano.org/commands/rpc-protocol/#account_history)
+#[derive(Debug, Clone, Deserialize)]
+pub struct AccountHistory {
+    pub account: Address,
+    #[serde(deserialize_with = "deserialize_empty_as_default")]
+    pub history: Vec<HistoryEntry>,
+    /// Head of the next page, when walking the chain backwards
+    pub previous: Option<BlockHash>,
+    /// Head of the next page, when walking the chain forwards
+    pub next: Option<BlockHash>,
+}
+
+/// A page starting at a fixed block and going backwards can no longer change once all its blocks are confirmed
+fn history_caching(request: &Value, response: &Value) -> Caching {
+    let fixed = request.get("head").is_some() && request["reverse"] != "true";
+    let confirmed = match response["history"].as_array() {
+        Some(history) => !history.is_empty() && history.iter().all(|entry| entry["confirmed"] == "true" || entry["confirmed"] == true),
+        None => false,
+    };
+    if fixed && confirmed {
+        Caching::Forever
+    } else {
+        Caching::Volatile
+    }
+}
+
+impl BananoApi {
+    /// Returns up to `count` blocks of the chain of `account`, from the most recent one unless
+    /// `options` say otherwise.
+    ///
+    /// Pages fully confirmed and starting at a given `head` are cached for good, see
+    /// [with_cache](BananoApi::with_cache).
+    pub async fn account_history(&self, account: &Address, count: u64, options: &AccountHistoryOptions) -> Result<AccountHistory, Error> {
+        let mut request = json!({
+            "action": "account_history",
+            "account": account,
+            "count": count.to_string(),
+            "raw": options.raw.to_string(),
+            "reverse": options.reverse.to_string(),
+        });
+        if let Some(head) = &options.head {
+            request["head"] = json!(head);
+        }
+        if let Some(offset) = options.offset {
+            request["offset"] = json!(offset.to_string());
+        }
+        if !options.account_filter.is_empty() {
+            request["account_filter"] = json!(options.account_filter);
+        }
+        let caching_request = request.clone();
+        self.cached_rpc(request, move |response| history_caching(&caching_request, response)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_account_history() {
+        let json = r#"{
+            "account": "ban_3iwi45me3cgo9aza9wx5f7rder37hw11xtc1ek8psqxw5oxb8cujjad6qp9y",
+            "history": [{
+                "type": "send",
+                "account": "ban_3t6k35gi95xu6tergt6p69ck76ogmitsa8mnijtpxm9fkcm736xtoncuohr3",
+                "amount": "100000000000000000000000000000",
+                "local_timestamp": "1624270400",
+                "height": "2",
+                "hash": "C251CB179C2267120710E79243159172D51F96B49865C391E41AA3694BF40835",
+                "confirmed": "true"
+            }],
+            "previous": "40DB7EC1F71F7B3B66982007F20E687148BDB875E533121259C0BF69AEFE88D3"
+        }"#;
+        let history: AccountHistory = serde_json::from_str(json).unwrap();
+        assert_eq!(history.history.len(), 1);
+        assert_eq!(history.history[0].kind, BlockType::Send);
+        assert_eq!(history.history[0].height, 2);
+        assert!(history.history[0].confirmed);
+        assert!(history.previous.is_some());
+
+        let empty: AccountHistory = serde_json::from_str(r#"{"account": "ban_3iwi45me3cgo9aza9wx5f7rder37hw11xtc1ek8psqxw5oxb8cujjad6qp9y", "history": ""}"#).unwrap();
+        assert!(empty.history.is_empty());
+    }
+}
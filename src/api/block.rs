@@ -0,0 +1,172 @@
+use crate::{Address, BananoApi, Error, Raw, encoding::deserialize_bool, types::{BlockHash, BlockSubtype, StateBlock}};
+use super::cache::Caching;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use serde_with::serde_as;
+use std::collections::HashMap;
+
+/// Options for [process](crate::BananoApi::process)
+///
+/// [Nano documentation](https://docs.nano.org/commands/rpc-protocol/#process)
+#[derive(Debug, Clone, Default)]
+pub struct ProcessOptions {
+    /// Subtype of the block, checked by the node against the block contents
+    pub subtype: Option<BlockSubtype>,
+    /// Let the node regenerate work if the block is not confirmed quickly enough
+    pub watch_work: Option<bool>,
+    /// Do not wait for the node to process the block
+    pub is_async: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ProcessResponse {
+    pub hash: Option<BlockHash>,
+}
+
+/// Contents of a block, legacy blocks (from before state blocks) are left untyped
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum BlockContents {
+    State(StateBlock),
+    Legacy(Value),
+}
+
+/// Block info
+///
+/// [Nano documentation](https://docs.nano.org/commands/rpc-protocol/#block_info)
+#[serde_as]
+#[derive(Debug, Clone, Deserialize)]
+pub struct BlockInfo {
+    pub block_account: Address,
+    pub amount: Option<Raw>,
+    pub balance: Raw,
+    #[serde(with = "serde_with::rust::display_fromstr")]
+    pub height: u64,
+    #[serde_as(as = "serde_with::TimestampSeconds<String>")]
+    pub local_timestamp: DateTime<Utc>,
+    /// Next block of the account chain, if any
+    pub successor: Option<BlockHash>,
+    #[serde(deserialize_with = "deserialize_bool")]
+    pub confirmed: bool,
+    pub contents: BlockContents,
+    pub subtype: Option<BlockSubtype>,
+}
+
+impl BlockInfo {
+    /// Whether the successor returned by the node designates an actual block
+    pub fn has_successor(&self) -> bool {
+        matches!(&self.successor, Some(successor) if *successor != BlockHash::zero())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct BlocksInfoResponse {
+    pub blocks: HashMap<BlockHash, BlockInfo>,
+}
+
+/// A block never changes once confirmed, except for its successor which is set only once
+fn block_caching(info: &Value) -> Caching {
+    let confirmed = matches!(&info["confirmed"], Value::Bool(true)) || info["confirmed"] == "true";
+    let has_successor = matches!(info["successor"].as_str(), Some(successor) if successor.bytes().any(|c| c != b'0'));
+    if confirmed && has_successor {
+        Caching::Forever
+    } else {
+        Caching::Volatile
+    }
+}
+
+fn block_info_request(hash: &BlockHash) -> Value {
+    json!({
+        "action": "block_info",
+        "json_block": "true",
+        "hash": hash,
+    })
+}
+
+impl BananoApi {
+    /// Returns information about the block `hash`
+    pub async fn block_info(&self, hash: &BlockHash) -> Result<BlockInfo, Error> {
+        self.cached_rpc(block_info_request(hash), block_caching).await
+    }
+
+    /// Returns information about each block of `hashes`. The node answers "Block not found"
+    /// if any of them is unknown.
+    ///
+    /// With a cache, only blocks missing from it are requested, and they are cached one by one.
+    pub async fn blocks_info(&self, hashes: &[BlockHash]) -> Result<HashMap<BlockHash, BlockInfo>, Error> {
+        let mut blocks = HashMap::new();
+        let mut missing = Vec::new();
+        for hash in hashes {
+            match &self.cache {
+                Some(cache) => match cache.get(&block_info_request(hash)).await {
+                    Some(info) => {
+                        blocks.insert(hash.clone(), serde_json::from_value(info)?);
+                    }
+                    None => missing.push(hash),
+                },
+                None => missing.push(hash),
+            }
+        }
+        if missing.is_empty() {
+            return Ok(blocks);
+        }
+
+        let request = json!({
+            "action": "blocks_info",
+            "json_block": "true",
+            "hashes": missing,
+        });
+        let response: Value = self.rpc(request).await?;
+        if let (Some(cache), Some(infos)) = (&self.cache, response["blocks"].as_object()) {
+            for (hash, info) in infos {
+                let request = block_info_request(&hash.parse()?);
+                cache.insert(&request, info.clone(), block_caching(info)).await;
+            }
+        }
+        let response: BlocksInfoResponse = serde_json::from_value(response)?;
+        blocks.extend(response.blocks);
+        Ok(blocks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn deserialize_blocks_info() {
+        let json = r#"{"blocks": {"C251CB179C2267120710E79243159172D51F96B49865C391E41AA3694BF40835": {
+            "block_account": "ban_3iwi45me3cgo9aza9wx5f7rder37hw11xtc1ek8psqxw5oxb8cujjad6qp9y",
+            "amount": "100000000000000000000000000000",
+            "balance": "9900000000000000000000000000000",
+            "height": "2",
+            "local_timestamp": "1624270400",
+            "successor": "0000000000000000000000000000000000000000000000000000000000000000",
+            "confirmed": "true",
+            "contents": {
+                "type": "state",
+                "account": "ban_3iwi45me3cgo9aza9wx5f7rder37hw11xtc1ek8psqxw5oxb8cujjad6qp9y",
+                "previous": "40DB7EC1F71F7B3B66982007F20E687148BDB875E533121259C0BF69AEFE88D3",
+                "representative": "ban_3t6k35gi95xu6tergt6p69ck76ogmitsa8mnijtpxm9fkcm736xtoncuohr3",
+                "balance": "9900000000000000000000000000000",
+                "link": "E89208DD038FBB269987689621D52292AE9C35941A7484756ECCED92A65093BA",
+                "link_as_account": "ban_3t6k35gi95xu6tergt6p69ck76ogmitsa8mnijtpxm9fkcm736xtoncuohr3",
+                "signature": "CC1A64F14790A4A9EBC2AFEC2E1D0446211027E180E7597AADEE778BB67A8659A057C165BA006DC7B8B4D59EA264B4222800ED2925FE889FD3F2234C57D08F04",
+                "work": "2bf29ef00786a6bc"
+            },
+            "subtype": "send"
+        }}}"#;
+        let response: BlocksInfoResponse = serde_json::from_str(json).unwrap();
+        let hash = BlockHash::from_str("C251CB179C2267120710E79243159172D51F96B49865C391E41AA3694BF40835").unwrap();
+        let info = &response.blocks[&hash];
+        assert!(info.confirmed);
+        assert!(!info.has_successor());
+        assert_eq!(info.subtype, Some(BlockSubtype::Send));
+        match &info.contents {
+            BlockContents::State(block) => assert_eq!(block.hash().unwrap(), hash),
+            BlockContents::Legacy(_) => panic!("expected a state block"),
+        }
+    }
+}
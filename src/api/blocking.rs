@@ -0,0 +1,198 @@
+//! Synchronous Banano API, available with the `blocking` feature
+//!
+//! # Example:
+//! ```no_run
+//! use banano_rs::{Address, api::BananoApiBlocking};
+//!
+//! let banano = BananoApiBlocking::new("https://kaliumapi.appditto.com/api".into());
+//! let address = Address("ban_1hgtqu7cmgxb66ta4gxt7coimqcxp86nzi5b7u14ip9zzpqr16a3dbqdja1f".into());
+//! let account_balance = banano.account_balance(&address).unwrap();
+//! ```
+
+use crate::{Address, BananoApi, Error, Raw, types::{BlockHash, Difficulty, QualifiedRoot, StateBlock, Work}};
+use super::*;
+use futures::{Stream, StreamExt};
+use indexmap::IndexMap;
+use std::{collections::HashMap, net::SocketAddr, pin::Pin, sync::Arc, time::Duration};
+use tokio::runtime::{Builder, Runtime};
+
+/// Methods blocking on their [BananoApi] counterpart
+macro_rules! blocking {
+    ($api:ident; $(fn $name:ident(&self $(, $arg:ident: $type:ty)*) -> $output:ty;)*) => {
+        $(
+            #[doc = concat!("Blocking version of [", stringify!($api), "::", stringify!($name), "]")]
+            pub fn $name(&self $(, $arg: $type)*) -> Result<$output, Error> {
+                self.runtime.block_on(self.api.$name($($arg),*))
+            }
+        )*
+    };
+}
+
+/// Banano API blocking the current thread until the node answers
+///
+/// Requests run on a runtime owned by the client, so it must not be used from async code:
+/// blocking on a runtime from within another one panics.
+#[derive(Clone)]
+pub struct BananoApiBlocking {
+    api: BananoApi,
+    runtime: Arc<Runtime>,
+}
+
+impl BananoApiBlocking {
+    /// Instanciate Banano API using a RPC API URL
+    ///
+    /// # Example:
+    /// ```
+    /// let banano = banano_rs::api::BananoApiBlocking::new("https://kaliumapi.appditto.com/api".into());
+    /// ```
+    pub fn new(rpc_api: String) -> Self {
+        Self::from_api(BananoApi::new(rpc_api))
+    }
+
+    /// Block on the requests of an already configured `api`
+    ///
+    /// # Example:
+    /// ```
+    /// use banano_rs::{BananoApi, api::{BananoApiBlocking, RateLimit}};
+    ///
+    /// let api = BananoApi::new("https://kaliumapi.appditto.com/api".into())
+    ///     .with_rate_limit(RateLimit::new(5.0, 10));
+    /// let banano = BananoApiBlocking::from_api(api);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the runtime cannot be created.
+    pub fn from_api(api: BananoApi) -> Self {
+        let runtime = Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("failed to create the runtime of the blocking client");
+        BananoApiBlocking {
+            api,
+            runtime: Arc::new(runtime),
+        }
+    }
+
+    /// The asynchronous API used underneath
+    pub fn api(&self) -> &BananoApi {
+        &self.api
+    }
+
+    blocking! { BananoApi;
+        fn account_balance(&self, account: &Address) -> AccountBalance;
+        fn account_block_count(&self, account: &Address) -> AccountBlockCount;
+        fn account_info(&self, account: &Address) -> AccountInfo;
+        fn account_history(&self, account: &Address, count: u64, options: &AccountHistoryOptions) -> AccountHistory;
+        fn process(&self, block: &StateBlock, options: ProcessOptions) -> BlockHash;
+        fn block_info(&self, hash: &BlockHash) -> BlockInfo;
+        fn blocks_info(&self, hashes: &[BlockHash]) -> HashMap<BlockHash, BlockInfo>;
+        fn block_confirm(&self, hash: &BlockHash) -> bool;
+        fn confirmation_history(&self, hash: Option<&BlockHash>) -> ConfirmationHistory;
+        fn confirmation_active(&self, announcements: Option<u32>) -> ConfirmationActive;
+        fn confirmation_info(&self, root: &QualifiedRoot, contents: bool, representatives: bool) -> ConfirmationInfo;
+        fn wait_for_confirmation(&self, hash: &BlockHash, timeout: Duration) -> ();
+        fn chain(&self, block: &BlockHash, count: i64, offset: Option<u64>, reverse: bool) -> Vec<BlockHash>;
+        fn successors(&self, block: &BlockHash, count: i64, offset: Option<u64>, reverse: bool) -> Vec<BlockHash>;
+        fn frontiers(&self, account: &Address, count: u64) -> IndexMap<Address, BlockHash>;
+        fn frontier_count(&self) -> u64;
+        fn ledger(&self, options: &LedgerOptions) -> IndexMap<Address, LedgerAccount>;
+        fn version(&self) -> NodeVersion;
+        fn block_count(&self) -> BlockCount;
+        fn uptime(&self) -> Duration;
+        fn peers(&self) -> HashMap<SocketAddr, Peer>;
+        fn telemetry(&self) -> Telemetry;
+        fn telemetry_peers(&self) -> Vec<Telemetry>;
+        fn telemetry_peer(&self, address: &SocketAddr) -> Telemetry;
+        fn active_difficulty(&self) -> ActiveDifficulty;
+        fn available_supply(&self) -> Raw;
+        fn receivable(&self, account: &Address, options: ReceivableOptions) -> IndexMap<BlockHash, Receivable>;
+        fn pending(&self, account: &Address, options: ReceivableOptions) -> IndexMap<BlockHash, Receivable>;
+        fn representatives(&self, count: Option<u64>, sorting: bool) -> IndexMap<Address, Raw>;
+        fn representatives_online(&self) -> HashMap<Address, Raw>;
+        fn delegators(&self, representative: &Address) -> HashMap<Address, Raw>;
+        fn delegators_count(&self, representative: &Address) -> u64;
+        fn account_weight(&self, account: &Address) -> Raw;
+        fn account_representative(&self, account: &Address) -> Address;
+        fn confirmation_quorum(&self) -> ConfirmationQuorum;
+        fn work_generate(&self, root: &BlockHash, difficulty: Option<Difficulty>, multiplier: Option<f64>) -> WorkGenerate;
+        fn work_validate(&self, work: &Work, root: &BlockHash, difficulty: Option<Difficulty>) -> WorkValidate;
+        fn work_cancel(&self, root: &BlockHash) -> ();
+    }
+
+    /// Blocking version of [BananoApi::ledger_stream], iterating over the accounts of the ledger
+    pub fn ledger_stream(&self, options: LedgerOptions, page_size: u64) -> BlockingIter<'_, Result<(Address, LedgerAccount), Error>> {
+        BlockingIter {
+            stream: Box::pin(self.api.ledger_stream(options, page_size)),
+            runtime: &self.runtime,
+        }
+    }
+
+    /// Blocking version of [BananoApi::node_wallet]
+    #[cfg(feature = "node-wallet")]
+    pub fn node_wallet(&self) -> NodeWalletApiBlocking<'_> {
+        NodeWalletApiBlocking {
+            api: self.api.node_wallet(),
+            runtime: &self.runtime,
+        }
+    }
+}
+
+impl From<BananoApi> for BananoApiBlocking {
+    fn from(api: BananoApi) -> Self {
+        Self::from_api(api)
+    }
+}
+
+/// Iterator blocking on each item of a stream
+pub struct BlockingIter<'a, T> {
+    stream: Pin<Box<dyn Stream<Item = T> + 'a>>,
+    runtime: &'a Runtime,
+}
+
+impl<T> Iterator for BlockingIter<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.runtime.block_on(self.stream.next())
+    }
+}
+
+/// Blocking version of [NodeWalletApi]
+#[cfg(feature = "node-wallet")]
+pub struct NodeWalletApiBlocking<'a> {
+    api: NodeWalletApi<'a>,
+    runtime: &'a Runtime,
+}
+
+#[cfg(feature = "node-wallet")]
+impl NodeWalletApiBlocking<'_> {
+    blocking! { NodeWalletApi;
+        fn wallet_create(&self, seed: Option<&crate::types::Seed>) -> crate::types::WalletId;
+        fn wallet_add(&self, wallet: &crate::types::WalletId, key: &crate::types::PrivateKey, work: bool) -> Address;
+        fn account_create(&self, wallet: &crate::types::WalletId, index: Option<u32>) -> Address;
+        fn accounts_create(&self, wallet: &crate::types::WalletId, count: u32) -> Vec<Address>;
+        fn send(&self, wallet: &crate::types::WalletId, source: &Address, destination: &Address, amount: &Raw, id: Option<&str>) -> BlockHash;
+        fn receive(&self, wallet: &crate::types::WalletId, account: &Address, block: &BlockHash) -> BlockHash;
+        fn wallet_balances(&self, wallet: &crate::types::WalletId, threshold: Option<&Raw>) -> HashMap<Address, AccountBalance>;
+        fn wallet_representative_set(&self, wallet: &crate::types::WalletId, representative: &Address, update_existing_accounts: bool) -> bool;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn blocks_without_runtime() {
+        let banano = BananoApiBlocking::from_api(BananoApi::with_transport(MemoryTransport::new(|request| {
+            match request["action"].as_str() {
+                Some("block_count") => Ok(json!({"count": "1000", "unchecked": "0", "cemented": "1000"})),
+                _ => Ok(json!({"error": "Unknown command"})),
+            }
+        })));
+        assert_eq!(banano.block_count().unwrap().cemented, 1000);
+        assert!(matches!(banano.version(), Err(Error::NodeError(_))));
+    }
+}
@@ -0,0 +1,161 @@
+use crate::{BananoApi, Error};
+use async_trait::async_trait;
+use lru::LruCache;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::{sync::{Arc, Mutex}, time::{Duration, Instant}};
+
+/// Storage for RPC responses, keyed by request
+///
+/// [BananoApi](crate::BananoApi) uses a [MemoryCache] when given one with
+/// [with_cache](crate::BananoApi::with_cache). Implement this trait to share responses
+/// between processes, for instance through Redis.
+#[async_trait]
+pub trait Cache: Send + Sync {
+    /// Returns the response stored for `key`, unless it expired
+    async fn get(&self, key: &str) -> Option<Value>;
+    /// Store `value` for `key`, for `ttl` or as long as possible if `None`
+    async fn insert(&self, key: String, value: Value, ttl: Option<Duration>);
+}
+
+/// In-memory cache dropping the least recently used responses when full
+///
+/// # Example:
+/// ```
+/// use banano_rs::{BananoApi, api::MemoryCache};
+/// use std::time::Duration;
+///
+/// let banano = BananoApi::new("https://kaliumapi.appditto.com/api".into())
+///     .with_cache(MemoryCache::new(10_000), Duration::from_secs(5));
+/// ```
+pub struct MemoryCache {
+    entries: Mutex<LruCache<String, (Value, Option<Instant>)>>,
+}
+
+impl MemoryCache {
+    /// Cache holding up to `capacity` responses
+    pub fn new(capacity: usize) -> Self {
+        MemoryCache {
+            entries: Mutex::new(LruCache::new(capacity)),
+        }
+    }
+
+    /// Number of responses currently stored, including expired ones
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[async_trait]
+impl Cache for MemoryCache {
+    async fn get(&self, key: &str) -> Option<Value> {
+        let mut entries = self.entries.lock().unwrap();
+        match entries.get(&key.to_string()) {
+            Some((_, Some(expires_at))) if *expires_at <= Instant::now() => {
+                entries.pop(&key.to_string());
+                None
+            }
+            Some((value, _)) => Some(value.clone()),
+            None => None,
+        }
+    }
+
+    async fn insert(&self, key: String, value: Value, ttl: Option<Duration>) {
+        let expires_at = ttl.map(|ttl| Instant::now() + ttl);
+        self.entries.lock().unwrap().put(key, (value, expires_at));
+    }
+}
+
+#[async_trait]
+impl<T: Cache + ?Sized> Cache for Arc<T> {
+    async fn get(&self, key: &str) -> Option<Value> {
+        (**self).get(key).await
+    }
+
+    async fn insert(&self, key: String, value: Value, ttl: Option<Duration>) {
+        (**self).insert(key, value, ttl).await
+    }
+}
+
+/// How long a response can be reused
+pub(crate) enum Caching {
+    /// The response may change at any time, it is kept for the volatile TTL
+    Volatile,
+    /// The response will never change
+    Forever,
+}
+
+#[derive(Clone)]
+pub(crate) struct CacheLayer {
+    backend: Arc<dyn Cache>,
+    volatile_ttl: Duration,
+}
+
+impl CacheLayer {
+    pub(crate) fn new(backend: Arc<dyn Cache>, volatile_ttl: Duration) -> Self {
+        CacheLayer { backend, volatile_ttl }
+    }
+
+    pub(crate) async fn get(&self, request: &Value) -> Option<Value> {
+        self.backend.get(&request.to_string()).await
+    }
+
+    pub(crate) async fn insert(&self, request: &Value, response: Value, caching: Caching) {
+        let ttl = match caching {
+            Caching::Forever => None,
+            Caching::Volatile if self.volatile_ttl > Duration::ZERO => Some(self.volatile_ttl),
+            Caching::Volatile => return,
+        };
+        self.backend.insert(request.to_string(), response, ttl).await
+    }
+}
+
+impl BananoApi {
+    /// Send `request` unless its response is in the cache, caching the response according to
+    /// `caching`. Without cache this is the same as `rpc`.
+    pub(super) async fn cached_rpc<T, F>(&self, request: Value, caching: F) -> Result<T, Error>
+    where
+        T: DeserializeOwned,
+        F: FnOnce(&Value) -> Caching,
+    {
+        let cache = match &self.cache {
+            Some(cache) => cache,
+            None => return self.rpc(request).await,
+        };
+        if let Some(response) = cache.get(&request).await {
+            return Ok(serde_json::from_value(response)?);
+        }
+        let response: Value = self.rpc(request.clone()).await?;
+        cache.insert(&request, response.clone(), caching(&response)).await;
+        Ok(serde_json::from_value(response)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn evicts_and_expires() {
+        let cache = MemoryCache::new(2);
+        tokio_test::block_on(async {
+            cache.insert("a".into(), json!(1), None).await;
+            cache.insert("b".into(), json!(2), Some(Duration::from_millis(20))).await;
+            assert_eq!(cache.get("a").await, Some(json!(1)));
+            cache.insert("c".into(), json!(3), None).await;
+            // "b" was the least recently used
+            assert_eq!(cache.get("b").await, None);
+            assert_eq!(cache.len(), 2);
+
+            cache.insert("d".into(), json!(4), Some(Duration::from_millis(20))).await;
+            assert_eq!(cache.get("d").await, Some(json!(4)));
+            tokio::time::sleep(Duration::from_millis(30)).await;
+            assert_eq!(cache.get("d").await, None);
+        });
+    }
+}
@@ -0,0 +1,340 @@
+use crate::{Address, BananoApi, Error, Raw, encoding::{deserialize_bool, deserialize_empty_as_default}, types::{BlockHash, QualifiedRoot}};
+use super::BlockContents;
+use serde::Deserialize;
+use serde_json::json;
+use serde_with::{serde_as, DisplayFromStr};
+use std::{collections::HashMap, time::{Duration, Instant}};
+
+const FIRST_CONFIRMATION_POLL: Duration = Duration::from_millis(500);
+const MAX_CONFIRMATION_POLL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct BlockConfirmResponse {
+    #[serde(deserialize_with = "deserialize_bool")]
+    pub started: bool,
+}
+
+/// Recently confirmed elections
+///
+/// [Nano documentation](https://docs.nano.org/commands/rpc-protocol/#confirmation_history)
+#[derive(Debug, Deserialize)]
+pub struct ConfirmationHistory {
+    pub confirmation_stats: ConfirmationStats,
+    #[serde(deserialize_with = "deserialize_empty_as_default")]
+    pub confirmations: Vec<ConfirmedElection>,
+}
+
+#[serde_as]
+#[derive(Debug, Deserialize)]
+pub struct ConfirmationStats {
+    #[serde_as(as = "DisplayFromStr")]
+    pub count: u64,
+    /// Average duration of elections in milliseconds, missing when there are none
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default)]
+    pub average: Option<u64>,
+}
+
+#[serde_as]
+#[derive(Debug, Deserialize)]
+pub struct ConfirmedElection {
+    pub hash: BlockHash,
+    /// Duration of the election in milliseconds
+    #[serde_as(as = "DisplayFromStr")]
+    pub duration: u64,
+    /// Time of the confirmation, in milliseconds since the UNIX epoch
+    #[serde_as(as = "DisplayFromStr")]
+    pub time: u64,
+    pub tally: Raw,
+    pub final_tally: Option<Raw>,
+    #[serde_as(as = "DisplayFromStr")]
+    pub blocks: u32,
+    #[serde_as(as = "DisplayFromStr")]
+    pub voters: u32,
+    #[serde_as(as = "DisplayFromStr")]
+    pub request_count: u32,
+}
+
+/// Active elections
+///
+/// [Nano documentation](https://docs.nano.org/commands/rpc-protocol/#confirmation_active)
+#[serde_as]
+#[derive(Debug, Deserialize)]
+pub struct ConfirmationActive {
+    /// Roots of the active elections
+    #[serde(deserialize_with = "deserialize_empty_as_default")]
+    pub confirmations: Vec<QualifiedRoot>,
+    #[serde_as(as = "DisplayFromStr")]
+    pub unconfirmed: u64,
+    #[serde_as(as = "DisplayFromStr")]
+    pub confirmed: u64,
+}
+
+/// State of an active election
+///
+/// [Nano documentation](https://docs.nano.org/commands/rpc-protocol/#confirmation_info)
+#[serde_as]
+#[derive(Debug, Deserialize)]
+pub struct ConfirmationInfo {
+    #[serde_as(as = "DisplayFromStr")]
+    pub announcements: u32,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default)]
+    pub voters: Option<u32>,
+    pub last_winner: BlockHash,
+    pub total_tally: Raw,
+    pub final_tally: Option<Raw>,
+    pub blocks: HashMap<BlockHash, ElectionBlock>,
+}
+
+/// Candidate block of an election
+#[derive(Debug, Deserialize)]
+pub struct ElectionBlock {
+    pub tally: Raw,
+    pub final_tally: Option<Raw>,
+    /// Only available when `contents` is set in the request
+    pub contents: Option<BlockContents>,
+    /// Only available when `representatives` is set in the request
+    pub representatives: Option<HashMap<Address, Raw>>,
+}
+
+impl BananoApi {
+    /// Request confirmation of `hash` from the network, returns whether the election started
+    pub async fn block_confirm(&self, hash: &BlockHash) -> Result<bool, Error> {
+        let request = json!({
+            "action": "block_confirm",
+            "hash": hash,
+        });
+        let response: BlockConfirmResponse = self.rpc(request).await?;
+        Ok(response.started)
+    }
+
+    /// Returns recently confirmed elections, optionally only the one of `hash`
+    pub async fn confirmation_history(&self, hash: Option<&BlockHash>) -> Result<ConfirmationHistory, Error> {
+        let mut request = json!({
+            "action": "confirmation_history",
+        });
+        if let Some(hash) = hash {
+            request["hash"] = json!(hash);
+        }
+        self.rpc(request).await
+    }
+
+    /// Returns roots of active elections, optionally only those announced at least `announcements` times
+    pub async fn confirmation_active(&self, announcements: Option<u32>) -> Result<ConfirmationActive, Error> {
+        let mut request = json!({
+            "action": "confirmation_active",
+        });
+        if let Some(announcements) = announcements {
+            request["announcements"] = json!(announcements.to_string());
+        }
+        self.rpc(request).await
+    }
+
+    /// Returns the state of the election for `root`, with candidate block `contents`
+    /// and voting `representatives` when requested
+    pub async fn confirmation_info(&self, root: &QualifiedRoot, contents: bool, representatives: bool) -> Result<ConfirmationInfo, Error> {
+        let request = json!({
+            "action": "confirmation_info",
+            "json_block": "true",
+            "root": root,
+            "contents": contents.to_string(),
+            "representatives": representatives.to_string(),
+        });
+        self.rpc(request).await
+    }
+
+    /// Wait until the block `hash` is confirmed (cemented), polling the node with an increasing delay.
+    ///
+    /// Returns [Error::Timeout] if the block is still unconfirmed after `timeout`. Blocks unknown to
+    /// the node are waited for as well, as they may not have reached it yet.
+    ///
+    /// # Example:
+    /// ```no_run
+    /// use banano_rs::{BananoApi, types::BlockHash};
+    /// use std::{str::FromStr, time::Duration};
+    ///
+    /// # async fn run() -> banano_rs::Result<()> {
+    /// let banano = BananoApi::new("https://kaliumapi.appditto.com/api".into());
+    /// let hash = BlockHash::from_str("40DB7EC1F71F7B3B66982007F20E687148BDB875E533121259C0BF69AEFE88D3")?;
+    /// banano.wait_for_confirmation(&hash, Duration::from_secs(60)).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn wait_for_confirmation(&self, hash: &BlockHash, timeout: Duration) -> Result<(), Error> {
+        let deadline = Instant::now() + timeout;
+        let mut delay = FIRST_CONFIRMATION_POLL;
+        loop {
+            match self.blocks_info(std::slice::from_ref(hash)).await {
+                Ok(blocks) if blocks.get(hash).map(|info| info.confirmed).unwrap_or(false) => return Ok(()),
+                Ok(_) => {}
+                Err(Error::NodeError(error)) if error == "Block not found" => {}
+                Err(error) => return Err(error),
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(Error::Timeout);
+            }
+            tokio::time::sleep(delay.min(deadline - now)).await;
+            delay = (delay * 2).min(MAX_CONFIRMATION_POLL);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{api::MockNode, types::sample_block};
+    use super::*;
+    use std::str::FromStr;
+
+    macro_rules! aw {
+        ($e:expr) => {
+            tokio_test::block_on($e)
+        };
+    }
+
+    #[test]
+    fn waits_for_confirmation() {
+        let node = MockNode::empty().with_auto_confirm(false);
+        let address = Address(MockNode::ACCOUNT.into());
+        let hash = node.add_account(&address, Raw::new(1u128), &address).unwrap();
+        let api = node.api();
+        assert!(matches!(aw!(api.wait_for_confirmation(&hash, Duration::ZERO)), Err(Error::Timeout)));
+
+        let confirming = node.clone();
+        let confirmed = hash.clone();
+        let confirmer = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(100));
+            confirming.set_confirmed(&confirmed, true);
+        });
+        aw!(api.wait_for_confirmation(&hash, Duration::from_secs(5))).unwrap();
+        confirmer.join().unwrap();
+        assert!(node.requests_for("blocks_info").len() >= 3);
+    }
+
+    #[test]
+    fn waits_for_unknown_blocks() {
+        let node = MockNode::empty();
+        let hash = sample_block().hash().unwrap();
+        assert!(matches!(aw!(node.api().wait_for_confirmation(&hash, Duration::from_millis(10))), Err(Error::Timeout)));
+        assert_eq!(node.requests_for("blocks_info").len(), 2);
+
+        // other errors are not waited for
+        node.on("blocks_info", |_| Ok(json!({"error": "Unable to parse JSON"})));
+        assert!(matches!(aw!(node.api().wait_for_confirmation(&hash, Duration::from_secs(5))), Err(Error::NodeError(_))));
+    }
+
+    #[test]
+    fn confirms_blocks() {
+        let node = MockNode::empty().with_auto_confirm(false);
+        let address = Address(MockNode::ACCOUNT.into());
+        let hash = node.add_account(&address, Raw::new(1u128), &address).unwrap();
+        let api = node.api();
+        assert!(aw!(api.block_confirm(&hash)).unwrap());
+        aw!(api.wait_for_confirmation(&hash, Duration::ZERO)).unwrap();
+        assert!(matches!(aw!(api.block_confirm(&sample_block().hash().unwrap())), Err(Error::NodeError(_))));
+
+        let response: BlockConfirmResponse = serde_json::from_str(r#"{"started": "0"}"#).unwrap();
+        assert!(!response.started);
+    }
+
+    #[test]
+    fn deserialize_confirmation_history() {
+        let json = r#"{
+            "confirmation_stats": {"count": "2", "average": "5000"},
+            "confirmations": [
+                {
+                    "hash": "EA70B32C55C193345D625F766EEA2FCA52D3F2CCE0B3A30838CC543026BB0FEA",
+                    "duration": "4000",
+                    "time": "1544819986",
+                    "tally": "80394786589602980996311817874549318248",
+                    "blocks": "1",
+                    "voters": "37",
+                    "request_count": "2"
+                },
+                {
+                    "hash": "F2F8DA6D2CA0A4D78EB043A7A29E12BDE5B4CE7DE1B99A93A5210428EE5B8667",
+                    "duration": "6000",
+                    "time": "1544819988",
+                    "tally": "68921714529890443063672782079965877749",
+                    "final_tally": "68921714529890443063672782079965877749",
+                    "blocks": "1",
+                    "voters": "64",
+                    "request_count": "1"
+                }
+            ]
+        }"#;
+        let history: ConfirmationHistory = serde_json::from_str(json).unwrap();
+        assert_eq!(history.confirmation_stats.count, 2);
+        assert_eq!(history.confirmation_stats.average, Some(5000));
+        assert_eq!(history.confirmations[0].duration, 4000);
+        assert_eq!(history.confirmations[0].final_tally, None);
+        assert_eq!(history.confirmations[1].voters, 64);
+        assert!(history.confirmations[1].final_tally.is_some());
+
+        let history: ConfirmationHistory = serde_json::from_str(r#"{"confirmation_stats": {"count": "0"}, "confirmations": ""}"#).unwrap();
+        assert_eq!(history.confirmation_stats.average, None);
+        assert!(history.confirmations.is_empty());
+    }
+
+    #[test]
+    fn deserialize_confirmation_active() {
+        let json = r#"{
+            "confirmations": [
+                "8031B600827C5CC05FDC911C28BBAC12A0E096CCB30FA8324F56C123676281B28031B600827C5CC05FDC911C28BBAC12A0E096CCB30FA8324F56C123676281B2"
+            ],
+            "unconfirmed": "133",
+            "confirmed": "5"
+        }"#;
+        let active: ConfirmationActive = serde_json::from_str(json).unwrap();
+        assert_eq!(active.confirmations.len(), 1);
+        assert_eq!(active.unconfirmed, 133);
+        assert_eq!(active.confirmed, 5);
+
+        let active: ConfirmationActive = serde_json::from_str(r#"{"confirmations": "", "unconfirmed": "0", "confirmed": "0"}"#).unwrap();
+        assert!(active.confirmations.is_empty());
+    }
+
+    #[test]
+    fn deserialize_confirmation_info() {
+        let block = sample_block();
+        let hash = block.hash().unwrap();
+        let json = json!({
+            "announcements": "2",
+            "voters": "29",
+            "last_winner": hash,
+            "total_tally": "8039478658960298099631181787454931824",
+            "final_tally": "0",
+            "blocks": {
+                hash.to_string(): {
+                    "tally": "8039478658960298099631181787454931824",
+                    "final_tally": "0",
+                    "contents": block,
+                    "representatives": {
+                        "ban_3t6k35gi95xu6tergt6p69ck76ogmitsa8mnijtpxm9fkcm736xtoncuohr3": "8039478658960298099631181787454931824"
+                    }
+                }
+            }
+        });
+        let info: ConfirmationInfo = serde_json::from_value(json).unwrap();
+        assert_eq!(info.announcements, 2);
+        assert_eq!(info.voters, Some(29));
+        assert_eq!(info.last_winner, hash);
+        let candidate = &info.blocks[&hash];
+        assert_eq!(candidate.tally, Raw::from_str("8039478658960298099631181787454931824").unwrap());
+        assert!(matches!(&candidate.contents, Some(BlockContents::State(contents)) if contents.link == block.link));
+        assert_eq!(candidate.representatives.as_ref().unwrap().len(), 1);
+
+        // without contents and representatives
+        let json = format!(r#"{{
+            "announcements": "1",
+            "last_winner": "{0}",
+            "total_tally": "0",
+            "blocks": {{"{0}": {{"tally": "0"}}}}
+        }}"#, hash);
+        let info: ConfirmationInfo = serde_json::from_str(&json).unwrap();
+        assert_eq!(info.voters, None);
+        assert!(info.blocks[&hash].contents.is_none());
+        assert!(info.blocks[&hash].representatives.is_none());
+    }
+}
@@ -0,0 +1,308 @@
+use crate::Error;
+use super::{HttpTransport, RateLimit, RateLimitedTransport, Transport, retry};
+use async_trait::async_trait;
+use log::{info, warn};
+use serde_json::{json, Value};
+use std::{sync::{Mutex, atomic::{AtomicUsize, Ordering}}, time::{Duration, Instant}};
+
+const DEFAULT_RECHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+struct Endpoint {
+    name: String,
+    transport: Box<dyn Transport>,
+    /// When the endpoint failed or was last checked, `None` while it is healthy
+    failed_at: Mutex<Option<Instant>>,
+}
+
+impl Endpoint {
+    fn is_healthy(&self) -> bool {
+        self.failed_at.lock().unwrap().is_none()
+    }
+
+    fn mark_healthy(&self) {
+        *self.failed_at.lock().unwrap() = None;
+    }
+
+    fn mark_failed(&self) {
+        *self.failed_at.lock().unwrap() = Some(Instant::now());
+    }
+
+    /// Returns whether a failed endpoint is due for a new check, postponing the next one
+    fn take_recheck(&self, interval: Duration) -> bool {
+        let mut failed_at = self.failed_at.lock().unwrap();
+        match *failed_at {
+            Some(at) if at.elapsed() >= interval => {
+                *failed_at = Some(Instant::now());
+                true
+            }
+            _ => false,
+        }
+    }
+
+    async fn check(&self) -> bool {
+        let request = json!({
+            "action": "block_count",
+        });
+        match self.transport.request(&request).await {
+            Ok(response) if response.get("error").is_none() => {
+                info!("RPC endpoint {} is back", self.name);
+                self.mark_healthy();
+                true
+            }
+            _ => {
+                self.mark_failed();
+                false
+            }
+        }
+    }
+}
+
+/// Transport spreading requests over several RPC endpoints.
+///
+/// Requests go to healthy endpoints in turn. When an endpoint fails to answer (connection error,
+/// timeout...) the failing endpoint is left aside and, if the request can safely be sent again, it
+/// is sent to the next endpoint. Other requests, like `process`, fail and are left to the
+/// [RetryPolicy](crate::api::RetryPolicy), as the failing endpoint may have handled them anyway.
+/// Rate limited requests are sent to the next endpoint without leaving the busy one aside.
+/// Replies from the node, including `{"error": ...}` ones, are returned as is.
+///
+/// An endpoint left aside gets requests again once a `block_count` check succeeds. Checks run every
+/// recheck interval while [monitor](FailoverTransport::monitor) is running, and otherwise when a
+/// request comes after the interval.
+///
+/// # Example:
+/// ```no_run
+/// use banano_rs::{BananoApi, api::FailoverTransport};
+/// use std::{sync::Arc, time::Duration};
+///
+/// # async fn run() {
+/// let transport = Arc::new(FailoverTransport::new(vec![
+///     "https://kaliumapi.appditto.com/api".to_string(),
+///     "http://localhost:7072".to_string(),
+/// ])
+/// .with_recheck_interval(Duration::from_secs(10)));
+/// let monitored = transport.clone();
+/// tokio::spawn(async move { monitored.monitor().await });
+/// let banano = BananoApi::with_transport(transport);
+/// # }
+/// ```
+pub struct FailoverTransport {
+    endpoints: Vec<Endpoint>,
+    next: AtomicUsize,
+    recheck_interval: Duration,
+}
+
+impl FailoverTransport {
+    /// Transport over the HTTP RPC APIs at `urls`
+    pub fn new<I: IntoIterator<Item = String>>(urls: I) -> Self {
+        urls.into_iter().fold(
+            FailoverTransport {
+                endpoints: Vec::new(),
+                next: AtomicUsize::new(0),
+                recheck_interval: DEFAULT_RECHECK_INTERVAL,
+            },
+            |failover, url| failover.with_endpoint(url.clone(), HttpTransport::new(url)),
+        )
+    }
+
+    /// Add an endpoint reached through `transport`, identified by `name` in logs
+    pub fn with_endpoint<T: Transport + 'static>(mut self, name: String, transport: T) -> Self {
+        self.endpoints.push(Endpoint {
+            name,
+            transport: Box::new(transport),
+            failed_at: Mutex::new(None),
+        });
+        self
+    }
+
+    /// How long to wait before checking again an endpoint which failed, 30 seconds by default
+    pub fn with_recheck_interval(mut self, recheck_interval: Duration) -> Self {
+        self.recheck_interval = recheck_interval;
+        self
+    }
+
+    /// Throttle requests to each endpoint added so far according to `rate_limit`, see [RateLimitedTransport]
+    pub fn with_rate_limit(mut self, rate_limit: RateLimit) -> Self {
+        self.endpoints = self.endpoints
+            .into_iter()
+            .map(|endpoint| Endpoint {
+                transport: Box::new(RateLimitedTransport::new(endpoint.transport, rate_limit.clone())),
+                ..endpoint
+            })
+            .collect();
+        self
+    }
+
+    /// Returns each endpoint name with whether it is considered healthy
+    pub fn endpoints_health(&self) -> Vec<(String, bool)> {
+        self.endpoints
+            .iter()
+            .map(|endpoint| (endpoint.name.clone(), endpoint.is_healthy()))
+            .collect()
+    }
+
+    /// Check failed endpoints right away, regardless of the recheck interval
+    pub async fn check_endpoints(&self) {
+        for endpoint in self.endpoints.iter().filter(|endpoint| !endpoint.is_healthy()) {
+            endpoint.check().await;
+        }
+    }
+
+    /// Check failed endpoints every recheck interval, forever. Meant to be spawned alongside the
+    /// [BananoApi](crate::BananoApi) using this transport.
+    pub async fn monitor(&self) {
+        loop {
+            tokio::time::sleep(self.recheck_interval).await;
+            self.check_endpoints().await;
+        }
+    }
+
+    /// Endpoints in the order they should be tried for the next request
+    fn rotation(&self) -> impl Iterator<Item = &Endpoint> {
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+        let count = self.endpoints.len();
+        (0..count).map(move |i| &self.endpoints[(start + i) % count])
+    }
+}
+
+#[async_trait]
+impl Transport for FailoverTransport {
+    async fn request(&self, request: &Value) -> Result<Value, Error> {
+        self.request_with_endpoint(request).await.0
+    }
+
+    async fn request_with_endpoint(&self, request: &Value) -> (Result<Value, Error>, Option<String>) {
+        let idempotent = retry::is_idempotent(request);
+        let mut last_error = None;
+        let mut skipped = Vec::new();
+
+        for endpoint in self.rotation() {
+            if !endpoint.is_healthy() {
+                if !endpoint.take_recheck(self.recheck_interval) {
+                    skipped.push(endpoint);
+                    continue;
+                }
+                if !endpoint.check().await {
+                    continue;
+                }
+            }
+            match endpoint.transport.request(request).await {
+                Ok(response) => return (Ok(response), Some(endpoint.name.clone())),
+                // the endpoint works, it is only busy
+                Err(error @ Error::RateLimited { .. }) => last_error = Some((error, &endpoint.name)),
+                Err(error) => {
+                    warn!("RPC endpoint {} failed: {}", endpoint.name, error);
+                    endpoint.mark_failed();
+                    // the endpoint may have handled the request before failing
+                    if !idempotent {
+                        return (Err(error), Some(endpoint.name.clone()));
+                    }
+                    last_error = Some((error, &endpoint.name));
+                }
+            }
+        }
+
+        // every healthy endpoint failed, give a chance to those left aside
+        for endpoint in skipped {
+            match endpoint.transport.request(request).await {
+                Ok(response) => {
+                    endpoint.mark_healthy();
+                    return (Ok(response), Some(endpoint.name.clone()));
+                }
+                Err(error @ Error::RateLimited { .. }) => last_error = Some((error, &endpoint.name)),
+                Err(error) if !idempotent => return (Err(error), Some(endpoint.name.clone())),
+                Err(error) => last_error = Some((error, &endpoint.name)),
+            }
+        }
+
+        match last_error {
+            Some((error, name)) => (Err(error), Some(name.clone())),
+            None => (Err(Error::NoEndpointAvailable), None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::MemoryTransport;
+    use std::sync::{Arc, atomic::AtomicBool};
+
+    fn node(name: &'static str, up: Arc<AtomicBool>) -> MemoryTransport {
+        MemoryTransport::new(move |_| {
+            if up.load(Ordering::SeqCst) {
+                Ok(json!({ "node": name }))
+            } else {
+                Err(Error::Timeout)
+            }
+        })
+    }
+
+    #[test]
+    fn fails_over_and_rechecks() {
+        let first_up = Arc::new(AtomicBool::new(false));
+        let transport = FailoverTransport::new(vec![])
+            .with_endpoint("first".into(), node("first", first_up.clone()))
+            .with_endpoint("second".into(), node("second", Arc::new(AtomicBool::new(true))))
+            .with_recheck_interval(Duration::from_secs(3600));
+        let request = json!({"action": "version"});
+
+        // first endpoint is down, requests fail over to the second one
+        for _ in 0..3 {
+            let response = tokio_test::block_on(transport.request(&request)).unwrap();
+            assert_eq!(response["node"], "second");
+        }
+        assert_eq!(transport.endpoints_health(), vec![("first".into(), false), ("second".into(), true)]);
+
+        // once back and checked, the first endpoint gets requests again
+        first_up.store(true, Ordering::SeqCst);
+        tokio_test::block_on(transport.check_endpoints());
+        let nodes: Vec<Value> = (0..2)
+            .map(|_| tokio_test::block_on(transport.request(&request)).unwrap()["node"].clone())
+            .collect();
+        assert!(nodes.contains(&json!("first")));
+        assert!(nodes.contains(&json!("second")));
+    }
+
+    #[test]
+    fn does_not_replay_unsafe_requests() {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counted = requests.clone();
+        let transport = FailoverTransport::new(vec![])
+            .with_endpoint("first".into(), MemoryTransport::new(move |_| {
+                counted.fetch_add(1, Ordering::SeqCst);
+                Err(Error::Timeout)
+            }))
+            .with_endpoint("second".into(), node("second", Arc::new(AtomicBool::new(true))))
+            .with_recheck_interval(Duration::ZERO);
+        let process = json!({"action": "process"});
+
+        // the first endpoint may have published the block, it is not sent again elsewhere
+        let (response, endpoint) = tokio_test::block_on(transport.request_with_endpoint(&process));
+        assert!(matches!(response, Err(Error::Timeout)));
+        assert_eq!(endpoint.as_deref(), Some("first"));
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        // a failed recheck does not send the request to the checked endpoint
+        for _ in 0..2 {
+            assert_eq!(tokio_test::block_on(transport.request(&process)).unwrap()["node"], "second");
+        }
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn monitors_failed_endpoints() {
+        let first_up = Arc::new(AtomicBool::new(false));
+        let transport = FailoverTransport::new(vec![])
+            .with_endpoint("first".into(), node("first", first_up.clone()))
+            .with_endpoint("second".into(), node("second", Arc::new(AtomicBool::new(true))))
+            .with_recheck_interval(Duration::from_millis(10));
+        transport.request(&json!({"action": "version"})).await.unwrap();
+        transport.request(&json!({"action": "version"})).await.unwrap();
+        assert_eq!(transport.endpoints_health()[0], ("first".into(), false));
+
+        first_up.store(true, Ordering::SeqCst);
+        let _ = tokio::time::timeout(Duration::from_millis(50), transport.monitor()).await;
+        assert_eq!(transport.endpoints_health()[0], ("first".into(), true));
+    }
+}
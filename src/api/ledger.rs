@@ -0,0 +1,315 @@
+use crate::{Address, BananoApi, Error, Raw, encoding::deserialize_empty_as_default, types::BlockHash};
+use chrono::{DateTime, Utc};
+use futures::{Stream, TryStreamExt, stream};
+use indexmap::IndexMap;
+use serde::Deserialize;
+use serde_json::json;
+use serde_with::serde_as;
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct BlocksResponse {
+    #[serde(deserialize_with = "deserialize_empty_as_default")]
+    pub blocks: Vec<BlockHash>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct FrontiersResponse {
+    #[serde(deserialize_with = "deserialize_empty_as_default")]
+    pub frontiers: IndexMap<Address, BlockHash>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct FrontierCountResponse {
+    #[serde(with = "serde_with::rust::display_fromstr")]
+    pub count: u64,
+}
+
+/// Options for [ledger](crate::BananoApi::ledger)
+///
+/// [Nano documentation](https://docs.nano.org/commands/rpc-protocol/#ledger)
+#[derive(Debug, Clone, Default)]
+pub struct LedgerOptions {
+    /// Start listing accounts from this one
+    pub account: Option<Address>,
+    /// Maximum number of accounts to return
+    pub count: Option<u64>,
+    /// Also return the representative of each account
+    pub representative: bool,
+    /// Also return the voting weight of each account
+    pub weight: bool,
+    /// Also return the receivable amount of each account
+    pub receivable: bool,
+    /// Only return accounts modified since this date
+    pub modified_since: Option<DateTime<Utc>>,
+    /// Sort accounts by decreasing balance
+    pub sorting: bool,
+    /// Only return accounts with at least this balance
+    pub threshold: Option<Raw>,
+}
+
+/// Account as listed by [ledger](crate::BananoApi::ledger)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(from = "LedgerAccountResponse")]
+pub struct LedgerAccount {
+    pub frontier: BlockHash,
+    pub open_block: BlockHash,
+    pub representative_block: BlockHash,
+    pub balance: Raw,
+    pub modified_timestamp: DateTime<Utc>,
+    pub block_count: u64,
+    /// Only available when `representative` is set in the options
+    pub representative: Option<Address>,
+    /// Only available when `weight` is set in the options
+    pub weight: Option<Raw>,
+    /// Only available when `receivable` is set in the options
+    pub receivable: Option<Raw>,
+}
+
+// recent nodes return the receivable amount under both keys, older ones only as `pending`
+#[serde_as]
+#[derive(Deserialize)]
+struct LedgerAccountResponse {
+    frontier: BlockHash,
+    open_block: BlockHash,
+    representative_block: BlockHash,
+    balance: Raw,
+    #[serde_as(as = "serde_with::TimestampSeconds<String>")]
+    modified_timestamp: DateTime<Utc>,
+    #[serde(with = "serde_with::rust::display_fromstr")]
+    block_count: u64,
+    representative: Option<Address>,
+    weight: Option<Raw>,
+    receivable: Option<Raw>,
+    pending: Option<Raw>,
+}
+
+impl From<LedgerAccountResponse> for LedgerAccount {
+    fn from(account: LedgerAccountResponse) -> Self {
+        LedgerAccount {
+            frontier: account.frontier,
+            open_block: account.open_block,
+            representative_block: account.representative_block,
+            balance: account.balance,
+            modified_timestamp: account.modified_timestamp,
+            block_count: account.block_count,
+            representative: account.representative,
+            weight: account.weight,
+            receivable: account.receivable.or(account.pending),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct LedgerResponse {
+    #[serde(deserialize_with = "deserialize_empty_as_default")]
+    pub accounts: IndexMap<Address, LedgerAccount>,
+}
+
+impl BananoApi {
+    /// Returns up to `count` block hashes following the chain of `block` backwards,
+    /// starting with `block` itself unless an `offset` is given
+    pub async fn chain(&self, block: &BlockHash, count: i64, offset: Option<u64>, reverse: bool) -> Result<Vec<BlockHash>, Error> {
+        self.chain_action("chain", block, count, offset, reverse).await
+    }
+
+    /// Returns up to `count` block hashes following the chain of `block` forwards,
+    /// starting with `block` itself unless an `offset` is given
+    pub async fn successors(&self, block: &BlockHash, count: i64, offset: Option<u64>, reverse: bool) -> Result<Vec<BlockHash>, Error> {
+        self.chain_action("successors", block, count, offset, reverse).await
+    }
+
+    async fn chain_action(&self, action: &str, block: &BlockHash, count: i64, offset: Option<u64>, reverse: bool) -> Result<Vec<BlockHash>, Error> {
+        let mut request = json!({
+            "action": action,
+            "block": block,
+            "count": count.to_string(),
+            "reverse": reverse.to_string(),
+        });
+        if let Some(offset) = offset {
+            request["offset"] = json!(offset.to_string());
+        }
+        let response: BlocksResponse = self.rpc(request).await?;
+        Ok(response.blocks)
+    }
+
+    /// Returns up to `count` accounts with their frontier block, in ledger order starting with `account`
+    pub async fn frontiers(&self, account: &Address, count: u64) -> Result<IndexMap<Address, BlockHash>, Error> {
+        let request = json!({
+            "action": "frontiers",
+            "account": account,
+            "count": count.to_string(),
+        });
+        let response: FrontiersResponse = self.rpc(request).await?;
+        Ok(response.frontiers)
+    }
+
+    /// Returns the number of accounts in the ledger
+    pub async fn frontier_count(&self) -> Result<u64, Error> {
+        let request = json!({
+            "action": "frontier_count",
+        });
+        let response: FrontierCountResponse = self.rpc(request).await?;
+        Ok(response.count)
+    }
+
+    /// Returns accounts of the ledger, in the order of the node: by decreasing balance when
+    /// [sorting](LedgerOptions::sorting) is set. This RPC is usually only enabled on nodes you control.
+    pub async fn ledger(&self, options: &LedgerOptions) -> Result<IndexMap<Address, LedgerAccount>, Error> {
+        let mut request = json!({
+            "action": "ledger",
+            "representative": options.representative.to_string(),
+            "weight": options.weight.to_string(),
+            "receivable": options.receivable.to_string(),
+            "pending": options.receivable.to_string(),
+            "sorting": options.sorting.to_string(),
+        });
+        if let Some(account) = &options.account {
+            request["account"] = json!(account);
+        }
+        if let Some(count) = options.count {
+            request["count"] = json!(count.to_string());
+        }
+        if let Some(modified_since) = options.modified_since {
+            request["modified_since"] = json!(modified_since.timestamp().to_string());
+        }
+        if let Some(threshold) = &options.threshold {
+            request["threshold"] = json!(threshold);
+        }
+        let response: LedgerResponse = self.rpc(request).await?;
+        Ok(response.accounts)
+    }
+
+    /// Stream every account of the ledger matching `options`, fetching `page_size` accounts at a time.
+    ///
+    /// Accounts are streamed in the ledger order, so `sorting` and `count` are ignored. A `page_size`
+    /// of 0 is treated as 1.
+    ///
+    /// # Example:
+    /// ```no_run
+    /// use banano_rs::{BananoApi, api::LedgerOptions};
+    /// use futures::TryStreamExt;
+    ///
+    /// # async fn run() -> banano_rs::Result<()> {
+    /// let banano = BananoApi::new("http://localhost:7072".into());
+    /// let accounts = banano.ledger_stream(LedgerOptions::default(), 1000);
+    /// futures::pin_mut!(accounts);
+    /// while let Some((address, account)) = accounts.try_next().await? {
+    ///     println!("{} owns {}", address.0, account.balance);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn ledger_stream(&self, options: LedgerOptions, page_size: u64) -> impl Stream<Item = Result<(Address, LedgerAccount), Error>> + '_ {
+        let page_size = page_size.max(1);
+        let options = LedgerOptions {
+            sorting: false,
+            count: Some(page_size),
+            ..options
+        };
+        stream::try_unfold((Some(options), None), move |(options, previous_last)| async move {
+            match options {
+                Some(options) => self.ledger_page(options, previous_last, page_size).await.map(Some),
+                None => Ok(None),
+            }
+        })
+        .map_ok(|page| stream::iter(page.into_iter().map(Ok)))
+        .try_flatten()
+    }
+
+    /// Fetch the page of [ledger_stream](BananoApi::ledger_stream) starting after `previous_last`,
+    /// along with the state needed to fetch the following one
+    async fn ledger_page(&self, mut options: LedgerOptions, previous_last: Option<Address>, page_size: u64) -> Result<(Vec<(Address, LedgerAccount)>, (Option<LedgerOptions>, Option<Address>)), Error> {
+        // pages after the first one start with the last account of the previous page
+        let count = page_size + previous_last.is_some() as u64;
+        options.count = Some(count);
+        let accounts = self.ledger(&options).await?;
+        let is_last_page = (accounts.len() as u64) < count;
+
+        let page: Vec<(Address, LedgerAccount)> = accounts
+            .into_iter()
+            .filter(|(address, _)| Some(address) != previous_last.as_ref())
+            .collect();
+
+        let last = page.last().map(|(address, _)| address.clone());
+        let next = match (&last, is_last_page) {
+            (Some(last), false) => Some(LedgerOptions {
+                account: Some(last.clone()),
+                ..options
+            }),
+            _ => None,
+        };
+        Ok((page, (next, last)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_ledger() {
+        let json = r#"{"accounts": {
+            "ban_1hgtqu7cmgxb66ta4gxt7coimqcxp86nzi5b7u14ip9zzpqr16a3dbqdja1f": {
+                "frontier": "40DB7EC1F71F7B3B66982007F20E687148BDB875E533121259C0BF69AEFE88D3",
+                "open_block": "40DB7EC1F71F7B3B66982007F20E687148BDB875E533121259C0BF69AEFE88D3",
+                "representative_block": "40DB7EC1F71F7B3B66982007F20E687148BDB875E533121259C0BF69AEFE88D3",
+                "balance": "9900000000000000000000000000000",
+                "modified_timestamp": "1624270400",
+                "block_count": "4",
+                "pending": "0"
+            }
+        }}"#;
+        let response: LedgerResponse = serde_json::from_str(json).unwrap();
+        let account = &response.accounts[&Address("ban_1hgtqu7cmgxb66ta4gxt7coimqcxp86nzi5b7u14ip9zzpqr16a3dbqdja1f".into())];
+        assert_eq!(account.block_count, 4);
+        assert_eq!(account.receivable, Some(Raw::zero()));
+        assert_eq!(account.representative, None);
+
+        let response: LedgerResponse = serde_json::from_str(r#"{"accounts": ""}"#).unwrap();
+        assert!(response.accounts.is_empty());
+    }
+
+    #[test]
+    fn deserialize_ledger_with_receivable_and_pending() {
+        let json = r#"{"accounts": {
+            "ban_1hgtqu7cmgxb66ta4gxt7coimqcxp86nzi5b7u14ip9zzpqr16a3dbqdja1f": {
+                "frontier": "40DB7EC1F71F7B3B66982007F20E687148BDB875E533121259C0BF69AEFE88D3",
+                "open_block": "40DB7EC1F71F7B3B66982007F20E687148BDB875E533121259C0BF69AEFE88D3",
+                "representative_block": "40DB7EC1F71F7B3B66982007F20E687148BDB875E533121259C0BF69AEFE88D3",
+                "balance": "9900000000000000000000000000000",
+                "modified_timestamp": "1624270400",
+                "block_count": "4",
+                "pending": "5",
+                "receivable": "5"
+            }
+        }}"#;
+        let response: LedgerResponse = serde_json::from_str(json).unwrap();
+        let account = &response.accounts[&Address("ban_1hgtqu7cmgxb66ta4gxt7coimqcxp86nzi5b7u14ip9zzpqr16a3dbqdja1f".into())];
+        assert_eq!(account.receivable, Some(Raw::new(5u128)));
+    }
+
+    #[tokio::test]
+    async fn keeps_the_order_of_the_node() {
+        use crate::{api::MockNode, types::{Account, PrivateKey, Seed}};
+        use futures::TryStreamExt;
+
+        let node = MockNode::empty();
+        for (index, balance) in [3u128, 7, 1, 9, 5].iter().enumerate() {
+            let address = Account::from(PrivateKey::from_seed(Seed([index as u8 + 1; 32]), 0)).address;
+            node.add_account(&address, Raw::new(*balance), &address).unwrap();
+        }
+        let api = node.api();
+        let accounts = api.ledger(&LedgerOptions { sorting: true, ..Default::default() }).await.unwrap();
+        let balances: Vec<u128> = accounts.values().map(|account| account.balance.to_u128()).collect();
+        assert_eq!(balances, vec![9, 7, 5, 3, 1]);
+
+        let streamed: Vec<(Address, LedgerAccount)> = api.ledger_stream(LedgerOptions::default(), 0).try_collect().await.unwrap();
+        assert_eq!(streamed.len(), 5);
+
+        let ordered: Vec<Address> = api.ledger(&LedgerOptions::default()).await.unwrap().into_keys().collect();
+        let paged: Vec<(Address, LedgerAccount)> = api.ledger_stream(LedgerOptions::default(), 2).try_collect().await.unwrap();
+        assert_eq!(paged.into_iter().map(|(address, _)| address).collect::<Vec<_>>(), ordered);
+        let frontiers = api.frontiers(&ordered[0], 5).await.unwrap();
+        assert_eq!(frontiers.into_keys().collect::<Vec<_>>(), ordered);
+    }
+}
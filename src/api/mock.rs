@@ -0,0 +1,980 @@
+//! Offline node for tests, available with the `test-utils` feature
+
+use crate::{Address, BananoApi, Error, Raw, encoding::blake2b, types::{BlockHash, BlockSubtype, Difficulty, Link, StateBlock, Work}};
+use super::{RetryPolicy, Transport};
+use async_trait::async_trait;
+use serde_json::{json, Map, Value};
+use std::{collections::HashMap, convert::TryFrom, str::FromStr, sync::{Arc, Mutex}};
+
+/// State of an account in the ledger of a [MockNode]
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockAccount {
+    pub frontier: BlockHash,
+    pub open_block: BlockHash,
+    pub representative_block: BlockHash,
+    pub representative: Address,
+    pub balance: Raw,
+    pub block_count: u64,
+    pub confirmation_height: u64,
+    /// Seconds since the Unix epoch
+    pub modified_timestamp: i64,
+}
+
+struct MockBlock {
+    block: StateBlock,
+    subtype: BlockSubtype,
+    amount: Raw,
+    height: u64,
+    local_timestamp: i64,
+    confirmed: bool,
+    successor: Option<BlockHash>,
+}
+
+struct ReceivableBlock {
+    amount: Raw,
+    source: Address,
+}
+
+type Handler = dyn Fn(&Value) -> Result<Value, Error> + Send + Sync;
+
+struct State {
+    accounts: HashMap<Address, MockAccount>,
+    blocks: HashMap<BlockHash, MockBlock>,
+    receivable: HashMap<Address, HashMap<BlockHash, ReceivableBlock>>,
+    overrides: HashMap<String, Arc<Handler>>,
+    requests: Vec<Value>,
+    work_threshold: Option<Difficulty>,
+    auto_confirm: bool,
+}
+
+/// Node answering RPC requests from memory, to test code using [BananoApi] offline
+///
+/// The node keeps a small ledger: published blocks are checked (signature, previous block,
+/// balance) and update accounts, so sending funds makes them receivable by the destination.
+/// Node-wide actions like `version` or `telemetry` return canned responses. Any action can
+/// be answered differently with [on](MockNode::on).
+///
+/// # Example:
+/// ```
+/// use banano_rs::{Address, api::MockNode};
+///
+/// let node = MockNode::new();
+/// let banano = node.api();
+/// let address = Address(MockNode::ACCOUNT.into());
+/// let balance = tokio_test::block_on(banano.account_balance(&address)).unwrap();
+/// assert_eq!(balance.balance, MockNode::account().balance);
+/// ```
+#[derive(Clone)]
+pub struct MockNode {
+    state: Arc<Mutex<State>>,
+}
+
+impl Default for MockNode {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockNode {
+    /// Account present in the ledger of [new](MockNode::new)
+    pub const ACCOUNT: &'static str = "ban_1hgtqu7cmgxb66ta4gxt7coimqcxp86nzi5b7u14ip9zzpqr16a3dbqdja1f";
+    /// Representative of [ACCOUNT](MockNode::ACCOUNT)
+    pub const REPRESENTATIVE: &'static str = "ban_1fomoz167m7o38gw4rzt7hz67oq6itejpt4yocrfywujbpatd711cjew8gjj";
+    /// Wallet returned by the node wallet actions
+    pub const WALLET: &'static str = "000D1BAEC8EC208142C99059B393051BAC8380F9B5A2E6B2489A277D81789F3F";
+
+    /// State of [ACCOUNT](MockNode::ACCOUNT) in the ledger of [new](MockNode::new): 99 BAN,
+    /// 4 blocks, last modified on 2021-06-21
+    pub fn account() -> MockAccount {
+        let block = BlockHash::from_str("40DB7EC1F71F7B3B66982007F20E687148BDB875E533121259C0BF69AEFE88D3").unwrap();
+        MockAccount {
+            frontier: block.clone(),
+            open_block: block.clone(),
+            representative_block: block,
+            representative: Address(Self::REPRESENTATIVE.into()),
+            balance: Raw::new(9900000000000000000000000000000u128),
+            block_count: 4,
+            confirmation_height: 4,
+            modified_timestamp: 1624270400,
+        }
+    }
+
+    /// Node knowing [ACCOUNT](MockNode::ACCOUNT)
+    pub fn new() -> Self {
+        let node = Self::empty();
+        node.set_account(&Address(Self::ACCOUNT.into()), Self::account());
+        node
+    }
+
+    /// Node with an empty ledger
+    pub fn empty() -> Self {
+        MockNode {
+            state: Arc::new(Mutex::new(State {
+                accounts: HashMap::new(),
+                blocks: HashMap::new(),
+                receivable: HashMap::new(),
+                overrides: HashMap::new(),
+                requests: Vec::new(),
+                work_threshold: None,
+                auto_confirm: true,
+            })),
+        }
+    }
+
+    /// API sending its requests to this node, without retries
+    pub fn api(&self) -> BananoApi {
+        BananoApi::with_transport(self.clone()).with_retry_policy(RetryPolicy::none())
+    }
+
+    /// Reject published blocks whose work does not reach `threshold`. Work is not checked by default.
+    pub fn with_work_threshold(self, threshold: Difficulty) -> Self {
+        self.state.lock().unwrap().work_threshold = Some(threshold);
+        self
+    }
+
+    /// Work threshold low enough for tests to generate work on the CPU right away
+    #[cfg(test)]
+    pub(crate) fn low_work_threshold() -> Difficulty {
+        Difficulty::DEFAULT.from_multiplier(1.0 / 4096.0)
+    }
+
+    /// Whether published blocks are confirmed right away, which is the default
+    pub fn with_auto_confirm(self, auto_confirm: bool) -> Self {
+        self.state.lock().unwrap().auto_confirm = auto_confirm;
+        self
+    }
+
+    /// Answer `action` with `handler` instead of the built-in behaviour
+    ///
+    /// # Example:
+    /// ```
+    /// use banano_rs::{Error, api::MockNode};
+    ///
+    /// let node = MockNode::new();
+    /// node.on("version", |_| Err(Error::Timeout));
+    /// assert!(tokio_test::block_on(node.api().version()).is_err());
+    /// ```
+    pub fn on<F>(&self, action: &str, handler: F)
+    where
+        F: Fn(&Value) -> Result<Value, Error> + Send + Sync + 'static,
+    {
+        self.state.lock().unwrap().overrides.insert(action.into(), Arc::new(handler));
+    }
+
+    /// Set the state of `address` in the ledger
+    pub fn set_account(&self, address: &Address, account: MockAccount) {
+        self.state.lock().unwrap().accounts.insert(address.clone(), account);
+    }
+
+    /// Open `address` with `balance`, delegated to `representative`, and returns the open block hash
+    pub fn add_account(&self, address: &Address, balance: Raw, representative: &Address) -> Result<BlockHash, Error> {
+        let block = StateBlock {
+            account: address.clone(),
+            previous: BlockHash::zero(),
+            representative: representative.clone(),
+            balance: balance.clone(),
+            link: BlockHash::zero().into(),
+            signature: None,
+            work: None,
+        };
+        let hash = block.hash()?;
+        let mut state = self.state.lock().unwrap();
+        let confirmed = state.auto_confirm;
+        state.blocks.insert(hash.clone(), MockBlock {
+            block,
+            subtype: BlockSubtype::Open,
+            amount: balance.clone(),
+            height: 1,
+            local_timestamp: chrono::Utc::now().timestamp(),
+            confirmed,
+            successor: None,
+        });
+        state.accounts.insert(address.clone(), MockAccount {
+            frontier: hash.clone(),
+            open_block: hash.clone(),
+            representative_block: hash.clone(),
+            representative: representative.clone(),
+            balance,
+            block_count: 1,
+            confirmation_height: confirmed as u64,
+            modified_timestamp: chrono::Utc::now().timestamp(),
+        });
+        Ok(hash)
+    }
+
+    /// Make `amount` sent by `source` receivable by `destination`, and returns the send block hash.
+    /// The ledger of `source` is left untouched.
+    pub fn add_receivable(&self, destination: &Address, source: &Address, amount: Raw) -> Result<BlockHash, Error> {
+        let mut state = self.state.lock().unwrap();
+        let block = StateBlock {
+            account: source.clone(),
+            // makes each send unique
+            previous: BlockHash::try_from(&*blake2b(BlockHash::LEN, &(state.blocks.len() as u64).to_le_bytes()))?,
+            representative: source.clone(),
+            balance: Raw::zero(),
+            link: destination.to_public_key()?.into(),
+            signature: None,
+            work: None,
+        };
+        let hash = block.hash()?;
+        let confirmed = state.auto_confirm;
+        state.blocks.insert(hash.clone(), MockBlock {
+            block,
+            subtype: BlockSubtype::Send,
+            amount: amount.clone(),
+            height: 1,
+            local_timestamp: chrono::Utc::now().timestamp(),
+            confirmed,
+            successor: None,
+        });
+        state.receivable.entry(destination.clone()).or_default().insert(hash.clone(), ReceivableBlock {
+            amount,
+            source: source.clone(),
+        });
+        Ok(hash)
+    }
+
+    /// Confirm the block `hash`, or mark it unconfirmed
+    pub fn set_confirmed(&self, hash: &BlockHash, confirmed: bool) {
+        if let Some(block) = self.state.lock().unwrap().blocks.get_mut(hash) {
+            block.confirmed = confirmed;
+        }
+    }
+
+    /// Returns the state of `address` in the ledger
+    pub fn account_state(&self, address: &Address) -> Option<MockAccount> {
+        self.state.lock().unwrap().accounts.get(address).cloned()
+    }
+
+    /// Returns a published block
+    pub fn block(&self, hash: &BlockHash) -> Option<StateBlock> {
+        self.state.lock().unwrap().blocks.get(hash).map(|block| block.block.clone())
+    }
+
+    /// Returns the blocks `address` can receive
+    pub fn receivable_blocks(&self, address: &Address) -> Vec<BlockHash> {
+        let state = self.state.lock().unwrap();
+        state.receivable.get(address).map(|blocks| blocks.keys().cloned().collect()).unwrap_or_default()
+    }
+
+    /// Returns every request received so far
+    pub fn requests(&self) -> Vec<Value> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    /// Returns the requests received so far for `action`
+    pub fn requests_for(&self, action: &str) -> Vec<Value> {
+        self.requests().into_iter().filter(|request| request["action"] == action).collect()
+    }
+
+    fn handle(&self, request: &Value) -> Result<Value, Error> {
+        let action = request["action"].as_str().unwrap_or_default().to_string();
+        let handler = {
+            let mut state = self.state.lock().unwrap();
+            state.requests.push(request.clone());
+            state.overrides.get(&action).cloned()
+        };
+        if let Some(handler) = handler {
+            return handler(request);
+        }
+
+        let mut state = self.state.lock().unwrap();
+        let response = match action.as_str() {
+            "account_balance" => state.account_balance(request),
+            "account_block_count" => state.account_block_count(request),
+            "account_info" => state.account_info(request),
+            "account_history" => state.account_history(request),
+            "account_representative" => state.account_representative(request),
+            "account_weight" => state.account_weight(request),
+            "delegators" => state.delegators(request),
+            "delegators_count" => state.delegators_count(request),
+            "representatives" => state.representatives(request),
+            "representatives_online" => state.representatives_online(),
+            "block_info" => state.block_info(request),
+            "blocks_info" => state.blocks_info(request),
+            "block_confirm" => state.block_confirm(request),
+            "chain" => state.chain(request, false),
+            "successors" => state.chain(request, true),
+            "frontiers" => state.frontiers(request),
+            "frontier_count" => Ok(json!({ "count": state.accounts.len().to_string() })),
+            "ledger" => state.ledger(request),
+            "receivable" | "pending" => state.receivable(request),
+            "process" => state.process(request),
+            "block_count" => Ok(json!({
+                "count": (state.blocks.len() as u64 + state.accounts.values().map(|account| account.block_count).sum::<u64>()).to_string(),
+                "unchecked": "0",
+                "cemented": state.blocks.values().filter(|block| block.confirmed).count().to_string(),
+            })),
+            "available_supply" => Ok(json!({ "available": state.total_balance().to_string() })),
+            "work_generate" => work_generate(request),
+            "work_validate" => work_validate(request),
+            "work_cancel" => Ok(json!({ "success": "" })),
+            "wallet_balances" => Ok(json!({ "balances": state.accounts.iter().map(|(address, account)| (address.0.clone(), json!({
+                "balance": account.balance,
+                "pending": "0",
+            }))).collect::<Map<String, Value>>() })),
+            other => Ok(canned(other)),
+        };
+        Ok(response.unwrap_or_else(|error| json!({ "error": error })))
+    }
+}
+
+#[async_trait]
+impl Transport for MockNode {
+    async fn request(&self, request: &Value) -> Result<Value, Error> {
+        self.handle(request)
+    }
+
+    fn endpoint(&self) -> Option<String> {
+        Some("mock".into())
+    }
+}
+
+/// Reply of the node, either a response or the text of an error reply
+type Reply = Result<Value, String>;
+
+fn address(request: &Value, field: &str) -> Result<Address, String> {
+    match request[field].as_str() {
+        Some(address) if Address(address.into()).to_public_key().is_ok() => Ok(Address(address.into())),
+        _ => Err("Bad account number".into()),
+    }
+}
+
+fn hash(value: &Value) -> Result<BlockHash, String> {
+    value.as_str().and_then(|hash| BlockHash::from_str(hash).ok()).ok_or_else(|| "Bad block hash".to_string())
+}
+
+fn number(request: &Value, field: &str) -> Option<u64> {
+    request[field].as_str().and_then(|count| count.parse().ok())
+}
+
+fn flag(request: &Value, field: &str) -> bool {
+    request[field] == "true" || request[field] == true
+}
+
+/// Source block of a receive, which is its link
+fn source_block(link: &Link) -> BlockHash {
+    BlockHash::try_from(link.as_bytes()).unwrap()
+}
+
+fn by_public_key(addresses: &mut [Address]) {
+    addresses.sort_by_key(|address| address.to_public_key().map(|key| key.0).unwrap_or_default());
+}
+
+impl State {
+    fn account(&self, address: &Address) -> Result<&MockAccount, String> {
+        self.accounts.get(address).ok_or_else(|| "Account not found".to_string())
+    }
+
+    fn block(&self, hash: &BlockHash) -> Result<&MockBlock, String> {
+        self.blocks.get(hash).ok_or_else(|| "Block not found".to_string())
+    }
+
+    fn receivable_total(&self, address: &Address) -> Raw {
+        self.receivable
+            .get(address)
+            .map(|blocks| blocks.values().fold(Raw::zero(), |total, block| total.checked_add(&block.amount).unwrap_or_else(Raw::max)))
+            .unwrap_or_else(Raw::zero)
+    }
+
+    fn total_balance(&self) -> Raw {
+        self.accounts.values().fold(Raw::zero(), |total, account| total.checked_add(&account.balance).unwrap_or_else(Raw::max))
+    }
+
+    fn weights(&self) -> HashMap<Address, Raw> {
+        let mut weights: HashMap<Address, Raw> = HashMap::new();
+        for account in self.accounts.values() {
+            let weight = weights.entry(account.representative.clone()).or_insert_with(Raw::zero);
+            *weight = weight.checked_add(&account.balance).unwrap_or_else(Raw::max);
+        }
+        weights
+    }
+
+    fn account_balance(&self, request: &Value) -> Reply {
+        let address = address(request, "account")?;
+        let balance = self.accounts.get(&address).map(|account| account.balance.clone()).unwrap_or_else(Raw::zero);
+        let receivable = self.receivable_total(&address);
+        Ok(json!({
+            "balance": balance,
+            "pending": receivable,
+            "receivable": receivable,
+        }))
+    }
+
+    fn account_block_count(&self, request: &Value) -> Reply {
+        let account = self.account(&address(request, "account")?)?;
+        Ok(json!({ "block_count": account.block_count.to_string() }))
+    }
+
+    fn account_info(&self, request: &Value) -> Reply {
+        let account = self.account(&address(request, "account")?)?;
+        let mut response = json!({
+            "frontier": account.frontier,
+            "open_block": account.open_block,
+            "representative_block": account.representative_block,
+            "balance": account.balance,
+            "modified_timestamp": account.modified_timestamp.to_string(),
+            "block_count": account.block_count.to_string(),
+            "account_version": "1",
+            "confirmation_height": account.confirmation_height.to_string(),
+            "confirmation_height_frontier": account.frontier,
+        });
+        if flag(request, "representative") {
+            response["representative"] = json!(account.representative);
+        }
+        Ok(response)
+    }
+
+    /// Hashes following the chain from `start`, backwards or forwards
+    fn walk(&self, start: &BlockHash, forwards: bool) -> Vec<BlockHash> {
+        let mut hashes = Vec::new();
+        let mut next = Some(start.clone());
+        while let Some(hash) = next {
+            let block = match self.blocks.get(&hash) {
+                Some(block) => block,
+                None => break,
+            };
+            hashes.push(hash);
+            next = if forwards {
+                block.successor.clone()
+            } else {
+                Some(block.block.previous.clone()).filter(|previous| *previous != BlockHash::zero())
+            };
+        }
+        hashes
+    }
+
+    /// Other account of a send or receive block
+    fn counterpart(&self, block: &MockBlock) -> Option<Address> {
+        match block.subtype {
+            BlockSubtype::Send => Some(block.block.link.to_address()),
+            BlockSubtype::Receive | BlockSubtype::Open => self.blocks
+                .get(&source_block(&block.block.link))
+                .map(|source| source.block.account.clone()),
+            _ => None,
+        }
+    }
+
+    fn account_history(&self, request: &Value) -> Reply {
+        let address = address(request, "account")?;
+        let raw = flag(request, "raw");
+        let reverse = flag(request, "reverse");
+        let count = number(request, "count").unwrap_or(u64::MAX) as usize;
+        let offset = number(request, "offset").unwrap_or(0) as usize;
+        let head = match request.get("head") {
+            Some(head) => hash(head)?,
+            None if reverse => self.account(&address)?.open_block.clone(),
+            None => match self.accounts.get(&address) {
+                Some(account) => account.frontier.clone(),
+                None => return Ok(json!({ "account": address, "history": "" })),
+            },
+        };
+        let filter: Vec<Address> = serde_json::from_value(request["account_filter"].clone()).unwrap_or_default();
+
+        let chain = self.walk(&head, reverse);
+        let mut history = Vec::new();
+        let mut last = None;
+        for hash in chain.iter().skip(offset) {
+            if history.len() == count {
+                break;
+            }
+            let block = &self.blocks[hash];
+            last = Some(block);
+            let counterpart = self.counterpart(block);
+            if !filter.is_empty() && !counterpart.as_ref().is_some_and(|counterpart| filter.contains(counterpart)) {
+                continue;
+            }
+            let kind = match block.subtype {
+                _ if raw => "state",
+                BlockSubtype::Send => "send",
+                BlockSubtype::Receive | BlockSubtype::Open => "receive",
+                _ => continue,
+            };
+            let mut entry = json!({
+                "type": kind,
+                "amount": block.amount,
+                "local_timestamp": block.local_timestamp.to_string(),
+                "height": block.height.to_string(),
+                "hash": hash,
+                "confirmed": block.confirmed.to_string(),
+            });
+            if let Some(counterpart) = counterpart {
+                entry["account"] = json!(counterpart);
+            }
+            if raw {
+                entry["subtype"] = json!(block.subtype);
+                entry["representative"] = json!(block.block.representative);
+                entry["balance"] = json!(block.block.balance);
+                entry["previous"] = json!(block.block.previous);
+                entry["link"] = json!(block.block.link);
+            }
+            history.push(entry);
+        }
+
+        let mut response = json!({
+            "account": address,
+            "history": if history.is_empty() { json!("") } else { json!(history) },
+        });
+        match last {
+            Some(block) if reverse => if let Some(successor) = &block.successor {
+                response["next"] = json!(successor);
+            },
+            Some(block) if block.block.previous != BlockHash::zero() => response["previous"] = json!(block.block.previous),
+            _ => {}
+        }
+        Ok(response)
+    }
+
+    fn account_representative(&self, request: &Value) -> Reply {
+        let account = self.account(&address(request, "account")?)?;
+        Ok(json!({ "representative": account.representative }))
+    }
+
+    fn account_weight(&self, request: &Value) -> Reply {
+        let address = address(request, "account")?;
+        Ok(json!({ "weight": self.weights().remove(&address).unwrap_or_else(Raw::zero) }))
+    }
+
+    fn delegators(&self, request: &Value) -> Reply {
+        let representative = address(request, "account")?;
+        let delegators: Map<String, Value> = self.accounts
+            .iter()
+            .filter(|(_, account)| account.representative == representative)
+            .map(|(address, account)| (address.0.clone(), json!(account.balance)))
+            .collect();
+        Ok(json!({ "delegators": if delegators.is_empty() { json!("") } else { json!(delegators) } }))
+    }
+
+    fn delegators_count(&self, request: &Value) -> Reply {
+        let representative = address(request, "account")?;
+        let count = self.accounts.values().filter(|account| account.representative == representative).count();
+        Ok(json!({ "count": count.to_string() }))
+    }
+
+    fn representatives(&self, request: &Value) -> Reply {
+        let mut weights: Vec<(Address, Raw)> = self.weights().into_iter().collect();
+        if flag(request, "sorting") {
+            weights.sort_by_key(|(_, weight)| std::cmp::Reverse(weight.to_u128()));
+        }
+        let count = number(request, "count").unwrap_or(u64::MAX) as usize;
+        let representatives: Map<String, Value> = weights.into_iter().take(count).map(|(address, weight)| (address.0, json!(weight))).collect();
+        Ok(json!({ "representatives": representatives }))
+    }
+
+    fn representatives_online(&self) -> Reply {
+        let representatives: Map<String, Value> = self.weights()
+            .into_iter()
+            .map(|(address, weight)| (address.0, json!({ "weight": weight })))
+            .collect();
+        Ok(json!({ "representatives": if representatives.is_empty() { json!("") } else { json!(representatives) } }))
+    }
+
+    fn block_json(&self, hash: &BlockHash) -> Reply {
+        let block = self.block(hash)?;
+        let mut contents = json!(block.block);
+        contents["link_as_account"] = json!(block.block.link.to_address());
+        Ok(json!({
+            "block_account": block.block.account,
+            "amount": block.amount,
+            "balance": block.block.balance,
+            "height": block.height.to_string(),
+            "local_timestamp": block.local_timestamp.to_string(),
+            "successor": block.successor.clone().unwrap_or_else(BlockHash::zero),
+            "confirmed": block.confirmed.to_string(),
+            "contents": contents,
+            "subtype": block.subtype,
+        }))
+    }
+
+    fn block_info(&self, request: &Value) -> Reply {
+        self.block_json(&hash(&request["hash"])?)
+    }
+
+    fn blocks_info(&self, request: &Value) -> Reply {
+        let hashes = request["hashes"].as_array().ok_or("Bad block hash")?;
+        let blocks = hashes
+            .iter()
+            .map(|value| {
+                let hash = hash(value)?;
+                Ok((hash.to_string(), self.block_json(&hash)?))
+            })
+            .collect::<Result<Map<String, Value>, String>>()?;
+        Ok(json!({ "blocks": blocks }))
+    }
+
+    fn block_confirm(&mut self, request: &Value) -> Reply {
+        let hash = hash(&request["hash"])?;
+        self.blocks.get_mut(&hash).ok_or("Block not found")?.confirmed = true;
+        Ok(json!({ "started": "1" }))
+    }
+
+    fn chain(&self, request: &Value, forwards: bool) -> Reply {
+        let block = hash(&request["block"])?;
+        self.block(&block)?;
+        let count = request["count"].as_str().and_then(|count| count.parse::<i64>().ok()).unwrap_or(-1);
+        let count = if count < 0 { usize::MAX } else { count as usize };
+        let offset = number(request, "offset").unwrap_or(0) as usize;
+        let mut blocks: Vec<BlockHash> = self.walk(&block, forwards != flag(request, "reverse"));
+        blocks = blocks.into_iter().skip(offset).take(count).collect();
+        Ok(json!({ "blocks": if blocks.is_empty() { json!("") } else { json!(blocks) } }))
+    }
+
+    /// Accounts in the ledger order, starting with `request["account"]`
+    fn ordered_accounts(&self, request: &Value) -> Result<Vec<Address>, String> {
+        let mut addresses: Vec<Address> = self.accounts.keys().cloned().collect();
+        by_public_key(&mut addresses);
+        if request.get("account").is_some() {
+            let start = address(request, "account")?.to_public_key().map_err(|error| error.to_string())?;
+            addresses.retain(|address| address.to_public_key().map(|key| key.0 >= start.0).unwrap_or(false));
+        }
+        Ok(addresses)
+    }
+
+    fn frontiers(&self, request: &Value) -> Reply {
+        let count = number(request, "count").unwrap_or(u64::MAX) as usize;
+        let frontiers: Map<String, Value> = self.ordered_accounts(request)?
+            .into_iter()
+            .take(count)
+            .map(|address| {
+                let frontier = json!(self.accounts[&address].frontier);
+                (address.0, frontier)
+            })
+            .collect();
+        Ok(json!({ "frontiers": if frontiers.is_empty() { json!("") } else { json!(frontiers) } }))
+    }
+
+    fn ledger(&self, request: &Value) -> Reply {
+        let threshold = request["threshold"].as_str().and_then(|threshold| threshold.parse::<u128>().ok()).unwrap_or(0);
+        let modified_since = request["modified_since"].as_str().and_then(|since| since.parse::<i64>().ok()).unwrap_or(0);
+        let mut addresses: Vec<Address> = self.ordered_accounts(request)?
+            .into_iter()
+            .filter(|address| {
+                let account = &self.accounts[address];
+                account.balance.to_u128() >= threshold && account.modified_timestamp >= modified_since
+            })
+            .collect();
+        if flag(request, "sorting") {
+            addresses.sort_by(|a, b| self.accounts[b].balance.to_u128().cmp(&self.accounts[a].balance.to_u128()));
+        }
+        let count = number(request, "count").unwrap_or(u64::MAX) as usize;
+        let weights = self.weights();
+
+        let accounts: Map<String, Value> = addresses
+            .into_iter()
+            .take(count)
+            .map(|address| {
+                let account = &self.accounts[&address];
+                let mut entry = json!({
+                    "frontier": account.frontier,
+                    "open_block": account.open_block,
+                    "representative_block": account.representative_block,
+                    "balance": account.balance,
+                    "modified_timestamp": account.modified_timestamp.to_string(),
+                    "block_count": account.block_count.to_string(),
+                });
+                if flag(request, "representative") {
+                    entry["representative"] = json!(account.representative);
+                }
+                if flag(request, "weight") {
+                    entry["weight"] = json!(weights.get(&address).cloned().unwrap_or_else(Raw::zero));
+                }
+                if flag(request, "receivable") || flag(request, "pending") {
+                    entry["receivable"] = json!(self.receivable_total(&address));
+                }
+                (address.0, entry)
+            })
+            .collect();
+        Ok(json!({ "accounts": if accounts.is_empty() { json!("") } else { json!(accounts) } }))
+    }
+
+    fn receivable(&self, request: &Value) -> Reply {
+        let address = address(request, "account")?;
+        let threshold = request["threshold"].as_str().and_then(|threshold| threshold.parse::<u128>().ok()).unwrap_or(0);
+        let count = number(request, "count").unwrap_or(u64::MAX) as usize;
+        let only_confirmed = request["include_only_confirmed"] != "false";
+
+        let mut blocks: Vec<(&BlockHash, &ReceivableBlock)> = self.receivable
+            .get(&address)
+            .map(|blocks| blocks.iter().collect())
+            .unwrap_or_default();
+        blocks.retain(|(hash, block)| {
+            block.amount.to_u128() >= threshold && (!only_confirmed || self.blocks.get(*hash).map_or(true, |block| block.confirmed))
+        });
+        if flag(request, "sorting") {
+            blocks.sort_by_key(|(_, block)| std::cmp::Reverse(block.amount.to_u128()));
+        }
+        let blocks: Map<String, Value> = blocks
+            .into_iter()
+            .take(count)
+            .map(|(hash, block)| {
+                let value = if flag(request, "source") {
+                    json!({ "amount": block.amount, "source": block.source })
+                } else {
+                    json!(block.amount)
+                };
+                (hash.to_string(), value)
+            })
+            .collect();
+        Ok(json!({ "blocks": if blocks.is_empty() { json!("") } else { json!(blocks) } }))
+    }
+
+    fn process(&mut self, request: &Value) -> Reply {
+        let block: StateBlock = serde_json::from_value(request["block"].clone()).map_err(|_| "Block is invalid".to_string())?;
+        let hash = block.hash().map_err(|error| error.to_string())?;
+        if self.blocks.contains_key(&hash) {
+            return Err("Old block".into());
+        }
+        let public_key = block.account.to_public_key().map_err(|_| "Bad account number".to_string())?;
+        match &block.signature {
+            Some(signature) if public_key.verify(hash.as_bytes(), signature) => {}
+            _ => return Err("Bad signature".into()),
+        }
+        if let Some(threshold) = &self.work_threshold {
+            let root = block.root().map_err(|error| error.to_string())?;
+            if !block.work.as_ref().is_some_and(|work| work.is_valid(&root, threshold)) {
+                return Err("Block work is less than threshold".into());
+            }
+        }
+
+        let previous_balance = match self.accounts.get(&block.account) {
+            Some(account) if account.frontier == block.previous => account.balance.clone(),
+            Some(_) if self.blocks.contains_key(&block.previous) => return Err("Fork".into()),
+            Some(_) => return Err("Gap previous block".into()),
+            None if block.is_open() => Raw::zero(),
+            None => return Err("Gap previous block".into()),
+        };
+
+        let (subtype, amount) = if block.balance.to_u128() < previous_balance.to_u128() {
+            (BlockSubtype::Send, previous_balance.checked_sub(&block.balance).unwrap())
+        } else if block.balance.to_u128() > previous_balance.to_u128() {
+            let amount = block.balance.checked_sub(&previous_balance).unwrap();
+            let source = source_block(&block.link);
+            let receivable = self.receivable.get(&block.account).and_then(|blocks| blocks.get(&source));
+            match receivable {
+                Some(receivable) if receivable.amount == amount => {}
+                Some(_) => return Err("Balance and amount delta do not match".into()),
+                None if self.blocks.contains_key(&source) => return Err("Unreceivable".into()),
+                None => return Err("Gap source block".into()),
+            }
+            self.receivable.get_mut(&block.account).unwrap().remove(&source);
+            let subtype = if block.is_open() { BlockSubtype::Open } else { BlockSubtype::Receive };
+            (subtype, amount)
+        } else if block.link == Link::from(BlockHash::zero()) {
+            (BlockSubtype::Change, Raw::zero())
+        } else {
+            return Err("Balance and amount delta do not match".into());
+        };
+        if let Some(expected) = request["subtype"].as_str() {
+            if json!(subtype) != expected {
+                return Err(format!("Invalid block subtype for {}", expected));
+            }
+        }
+
+        let now = chrono::Utc::now().timestamp();
+        if subtype == BlockSubtype::Send {
+            self.receivable.entry(block.link.to_address()).or_default().insert(hash.clone(), ReceivableBlock {
+                amount: amount.clone(),
+                source: block.account.clone(),
+            });
+        }
+        let height = match self.accounts.get_mut(&block.account) {
+            Some(account) => {
+                if account.representative != block.representative {
+                    account.representative_block = hash.clone();
+                }
+                account.frontier = hash.clone();
+                account.representative = block.representative.clone();
+                account.balance = block.balance.clone();
+                account.block_count += 1;
+                account.modified_timestamp = now;
+                if self.auto_confirm {
+                    account.confirmation_height = account.block_count;
+                }
+                account.block_count
+            }
+            None => {
+                self.accounts.insert(block.account.clone(), MockAccount {
+                    frontier: hash.clone(),
+                    open_block: hash.clone(),
+                    representative_block: hash.clone(),
+                    representative: block.representative.clone(),
+                    balance: block.balance.clone(),
+                    block_count: 1,
+                    confirmation_height: self.auto_confirm as u64,
+                    modified_timestamp: now,
+                });
+                1
+            }
+        };
+        if let Some(previous) = self.blocks.get_mut(&block.previous) {
+            previous.successor = Some(hash.clone());
+        }
+        self.blocks.insert(hash.clone(), MockBlock {
+            block,
+            subtype,
+            amount,
+            height,
+            local_timestamp: now,
+            confirmed: self.auto_confirm,
+            successor: None,
+        });
+        Ok(json!({ "hash": hash }))
+    }
+}
+
+/// Threshold of a `work_*` request
+fn requested_threshold(request: &Value) -> Difficulty {
+    match (request["difficulty"].as_str(), request["multiplier"].as_str()) {
+        (Some(difficulty), _) => difficulty.parse().unwrap_or_default(),
+        (None, Some(multiplier)) => Difficulty::DEFAULT.from_multiplier(multiplier.parse().unwrap_or(1.0)),
+        (None, None) => Difficulty::DEFAULT,
+    }
+}
+
+fn work_generate(request: &Value) -> Reply {
+    let root = hash(&request["hash"])?;
+    let work = Work::generate(&root, &requested_threshold(request));
+    let difficulty = work.difficulty(&root);
+    Ok(json!({
+        "work": work,
+        "difficulty": difficulty,
+        "multiplier": difficulty.multiplier(&Difficulty::DEFAULT).to_string(),
+        "hash": root,
+    }))
+}
+
+fn work_validate(request: &Value) -> Reply {
+    let root = hash(&request["hash"])?;
+    let work: Work = request["work"].as_str().and_then(|work| work.parse().ok()).ok_or("Bad work")?;
+    let difficulty = work.difficulty(&root);
+    let valid = work.is_valid(&root, &Difficulty::DEFAULT);
+    let mut response = json!({
+        "valid_all": if valid { "1" } else { "0" },
+        "valid_receive": if valid { "1" } else { "0" },
+        "difficulty": difficulty,
+        "multiplier": difficulty.multiplier(&Difficulty::DEFAULT).to_string(),
+    });
+    if request.get("difficulty").is_some() {
+        response["valid"] = json!(if work.is_valid(&root, &requested_threshold(request)) { "1" } else { "0" });
+    }
+    Ok(response)
+}
+
+/// Responses of actions which do not depend on the ledger
+fn canned(action: &str) -> Value {
+    let telemetry = json!({
+        "block_count": "5777903",
+        "cemented_count": "688819",
+        "unchecked_count": "443468",
+        "account_count": "620275",
+        "bandwidth_cap": "1572864",
+        "peer_count": "32",
+        "protocol_version": "18",
+        "uptime": "556896",
+        "genesis_block": "F61A79F89F5F8D4B30E8EB4E4E6A5C6BA7A1B7C5E43F6A6E8A3B4D3C7A2F0B1E",
+        "major_version": "23",
+        "minor_version": "0",
+        "patch_version": "0",
+        "pre_release_version": "0",
+        "maker": "0",
+        "timestamp": "1624270400000",
+        "active_difficulty": "fffffe0000000000",
+    });
+    match action {
+        "version" => json!({
+            "rpc_version": "1",
+            "store_version": "21",
+            "protocol_version": "18",
+            "node_vendor": "Banano V23.0",
+            "store_vendor": "LMDB 0.9.25",
+            "network": "live",
+            "network_identifier": "F61A79F89F5F8D4B30E8EB4E4E6A5C6BA7A1B7C5E43F6A6E8A3B4D3C7A2F0B1E",
+            "build_info": "mock",
+        }),
+        "uptime" => json!({ "seconds": "3600" }),
+        "peers" => json!({ "peers": {
+            "[::ffff:172.17.0.1]:7071": {
+                "protocol_version": "18",
+                "node_id": "node_1y7j5rdqhg99uyab1145gu3yur1ax35a3b6qr417yt8cd6n86uiw3d4whty3",
+                "type": "tcp",
+            },
+        }}),
+        "telemetry" => json!({
+            "metrics": [telemetry.clone()],
+        }).as_object().cloned().map(|mut metrics| {
+            // the aggregated and single peer forms share the fields of a metric
+            for (key, value) in telemetry.as_object().unwrap() {
+                metrics.insert(key.clone(), value.clone());
+            }
+            Value::Object(metrics)
+        }).unwrap(),
+        "active_difficulty" => json!({
+            "network_minimum": "fffffe0000000000",
+            "network_receive_minimum": "fffffe0000000000",
+            "network_current": "fffffe0000000000",
+            "network_receive_current": "fffffe0000000000",
+            "multiplier": "1",
+        }),
+        "confirmation_quorum" => json!({
+            "quorum_delta": "41469707173777717318245825935516662250",
+            "online_weight_quorum_percent": "50",
+            "online_weight_minimum": "60000000000000000000000000000000000000",
+            "online_stake_total": "82939414347555434636491651871033324568",
+            "peers_stake_total": "69026910610720098597176027400951402360",
+            "trended_stake_total": "81939414347555434636491651871033324568",
+        }),
+        "confirmation_history" => json!({
+            "confirmation_stats": { "count": "0" },
+            "confirmations": "",
+        }),
+        "confirmation_active" => json!({
+            "confirmations": "",
+            "unconfirmed": "0",
+            "confirmed": "0",
+        }),
+        "confirmation_info" => json!({ "error": "Active confirmation not found" }),
+        "wallet_create" => json!({ "wallet": MockNode::WALLET }),
+        "account_create" | "wallet_add" => json!({ "account": MockNode::ACCOUNT }),
+        "accounts_create" => json!({ "accounts": [MockNode::ACCOUNT] }),
+        "send" | "receive" => json!({ "block": "40DB7EC1F71F7B3B66982007F20E687148BDB875E533121259C0BF69AEFE88D3" }),
+        "wallet_representative_set" => json!({ "set": "1" }),
+        _ => json!({ "error": "Unknown command" }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{PrivateKey, PublicKey, Seed};
+
+    #[test]
+    fn sends_and_receives() {
+        let node = MockNode::empty();
+        let key = PrivateKey::from_seed(Seed([1; 32]), 0);
+        let sender = Address::from(PublicKey::from(&key));
+        let destination = Address(MockNode::ACCOUNT.into());
+        let open = node.add_account(&sender, Raw::new(100u32), &sender).unwrap();
+
+        let mut send = StateBlock {
+            account: sender.clone(),
+            previous: open.clone(),
+            representative: sender.clone(),
+            balance: Raw::new(60u32),
+            link: destination.to_public_key().unwrap().into(),
+            signature: None,
+            work: None,
+        };
+        // unsigned blocks are rejected
+        assert_eq!(node.handle(&json!({"action": "process", "block": send})).unwrap()["error"], "Bad signature");
+        send.sign(&key).unwrap();
+        let response = node.handle(&json!({"action": "process", "block": send, "subtype": "send"})).unwrap();
+        assert_eq!(response["hash"], json!(send.hash().unwrap()));
+        assert_eq!(node.handle(&json!({"action": "process", "block": send})).unwrap()["error"], "Old block");
+
+        assert_eq!(node.account_state(&sender).unwrap().balance, Raw::new(60u32));
+        assert_eq!(node.receivable_blocks(&destination), vec![send.hash().unwrap()]);
+        let history = node.handle(&json!({"action": "account_history", "account": sender, "count": "10"})).unwrap();
+        assert_eq!(history["history"][0]["amount"], "40");
+        assert_eq!(history["history"][0]["account"], json!(destination));
+    }
+}
@@ -1,7 +1,7 @@
 //! Banano API
 //!
 //! # Example:
-//! ```
+//! ```no_run
 //! use banano_rs::{
 //!   BananoApi,
 //!   Address
@@ -13,21 +13,67 @@
 //!     let banano = BananoApi::new("https://kaliumapi.appditto.com/api".into());
 //!     let address = Address("ban_1hgtqu7cmgxb66ta4gxt7coimqcxp86nzi5b7u14ip9zzpqr16a3dbqdja1f".into());
 //!     let account_balance = banano.account_balance(&address).await?;
-//!		Ok(())
+//!     Ok(())
 //! }
 //! ```
 
-use crate::{Error, types::Address};
+use crate::{Error, ProcessError, types::{Address, BlockHash, StateBlock}};
 pub use self::account::*;
-use reqwest::Client;
-use serde_json::json;
+pub use self::block::*;
+pub use self::builder::BananoApiBuilder;
+#[cfg(feature = "blocking")]
+pub use self::blocking::*;
+pub use self::cache::{Cache, MemoryCache};
+pub use self::confirmation::*;
+pub use self::failover::*;
+pub use self::ledger::*;
+#[cfg(any(test, feature = "test-utils"))]
+pub use self::mock::*;
+pub use self::node::*;
+pub use self::observer::*;
+pub use self::rate_limit::*;
+pub use self::receivable::*;
+pub use self::representatives::*;
+pub use self::retry::RetryPolicy;
+pub use self::transport::*;
+pub use self::work::*;
+#[cfg(feature = "node-wallet")]
+pub use self::wallet::*;
+use log::{debug, warn};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use self::cache::{CacheLayer, Caching};
+use std::{sync::Arc, time::{Duration, Instant}};
 
 mod account;
+mod block;
+mod builder;
+#[cfg(feature = "blocking")]
+mod blocking;
+mod cache;
+mod confirmation;
+mod failover;
+mod ledger;
+#[cfg(any(test, feature = "test-utils"))]
+mod mock;
+mod node;
+mod observer;
+mod rate_limit;
+mod receivable;
+mod representatives;
+mod retry;
+mod transport;
+mod work;
+#[cfg(feature = "node-wallet")]
+mod wallet;
 
 /// Banano API
+#[derive(Clone)]
 pub struct BananoApi {
-    rpc_api: String,
-    client: Client,
+    transport: Arc<dyn Transport>,
+    retry_policy: RetryPolicy,
+    cache: Option<CacheLayer>,
+    observers: Vec<Arc<dyn RpcObserver>>,
 }
 
 impl BananoApi {
@@ -38,24 +84,153 @@ impl BananoApi {
     /// let banano = banano_rs::BananoApi::new("https://kaliumapi.appditto.com/api".into());
     /// ```
     pub fn new(rpc_api: String) -> Self {
+        Self::with_transport(HttpTransport::new(rpc_api))
+    }
+
+    /// Configure Banano API using a RPC API URL, see [BananoApiBuilder]
+    ///
+    /// # Example:
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// let banano = banano_rs::BananoApi::builder("https://kaliumapi.appditto.com/api".into())
+    ///     .timeout(Duration::from_secs(5))
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn builder(rpc_api: String) -> BananoApiBuilder {
+        BananoApiBuilder::new(rpc_api)
+    }
+
+    /// Instanciate Banano API over several RPC API URLs, failing over from one to another
+    /// when they are unreachable. See [FailoverTransport].
+    ///
+    /// # Example:
+    /// ```
+    /// let banano = banano_rs::BananoApi::with_endpoints(vec![
+    ///     "https://kaliumapi.appditto.com/api".into(),
+    ///     "http://localhost:7072".into(),
+    /// ]);
+    /// ```
+    pub fn with_endpoints(rpc_apis: Vec<String>) -> Self {
+        Self::with_transport(FailoverTransport::new(rpc_apis))
+    }
+
+    /// Instanciate Banano API sending requests through `transport`
+    pub fn with_transport<T: Transport + 'static>(transport: T) -> Self {
         BananoApi {
-            rpc_api: rpc_api,
-            client: Client::new(),
+            transport: Arc::new(transport),
+            retry_policy: RetryPolicy::default(),
+            cache: None,
+            observers: Vec::new(),
         }
     }
 
-    /// Returns how many RAW is owned and how many have not yet been received by `account`
+    /// Retry requests failing because of the network according to `retry_policy`.
+    /// Only requests which are safe to send twice are retried, see [RetryPolicy].
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Throttle requests according to `rate_limit`, pausing them when the node answers with HTTP 429.
+    /// The limit applies to all endpoints together, see
+    /// [FailoverTransport::with_rate_limit] to limit each of them separately.
+    ///
+    /// # Example:
+    /// ```
+    /// use banano_rs::{BananoApi, api::RateLimit};
+    ///
+    /// let banano = BananoApi::new("https://kaliumapi.appditto.com/api".into())
+    ///     .with_rate_limit(RateLimit::new(5.0, 10));
+    /// ```
+    pub fn with_rate_limit(mut self, rate_limit: RateLimit) -> Self {
+        self.transport = Arc::new(RateLimitedTransport::new(self.transport, rate_limit));
+        self
+    }
+
+    /// Reuse responses stored in `cache` instead of querying the node again.
+    ///
+    /// Responses which can no longer change, like confirmed blocks, are kept as long as the cache
+    /// allows. Responses which may change, like [account_balance](BananoApi::account_balance),
+    /// are kept for `volatile_ttl`, which can be zero to never cache them.
+    pub fn with_cache<C: Cache + 'static>(mut self, cache: C, volatile_ttl: Duration) -> Self {
+        self.cache = Some(CacheLayer::new(Arc::new(cache), volatile_ttl));
+        self
+    }
+
+    /// Report every request sent to the node to `observer`, see [RpcObserver]
+    pub fn with_observer<O: RpcObserver + 'static>(mut self, observer: O) -> Self {
+        self.observers.push(Arc::new(observer));
+        self
+    }
+
+    /// Send `request` to the node once, turning `{"error": ...}` replies into [Error::NodeError]
+    async fn request(&self, request: &Value, attempt: u32) -> Result<Value, Error> {
+        let action = request["action"].as_str().unwrap_or_default();
+        #[cfg(feature = "tracing")]
+        let span = tracing::debug_span!(
+            "rpc",
+            action,
+            attempt,
+            endpoint = tracing::field::Empty,
+            latency_ms = tracing::field::Empty,
+            error = tracing::field::Empty,
+        );
+
+        let start = Instant::now();
+        let exchange = self.transport.request_with_endpoint(request);
+        #[cfg(feature = "tracing")]
+        let exchange = tracing::Instrument::instrument(exchange, span.clone());
+        let (response, endpoint) = exchange.await;
+        let response = response.and_then(|response| match response.get("error").and_then(Value::as_str) {
+            Some(error) => Err(Error::NodeError(error.into())),
+            None => Ok(response),
+        });
+        let latency = start.elapsed();
+
+        let endpoint = endpoint.as_deref();
+        let status = response.as_ref().map(|_| ()).map_err(ErrorClass::from);
+        match (&response, status) {
+            (Ok(_), _) => debug!("{} to {} took {:?}", action, endpoint.unwrap_or("?"), latency),
+            (Err(error), Err(ErrorClass::Node)) => debug!("{} to {} failed after {:?}: {}", action, endpoint.unwrap_or("?"), latency, error),
+            (Err(error), _) => warn!("{} to {} failed after {:?}: {}", action, endpoint.unwrap_or("?"), latency, error),
+        }
+        #[cfg(feature = "tracing")]
+        {
+            span.record("endpoint", endpoint.unwrap_or_default());
+            span.record("latency_ms", latency.as_millis() as u64);
+            if let Err(class) = status {
+                span.record("error", tracing::field::debug(class));
+            }
+        }
+        let event = RpcEvent {
+            action,
+            endpoint,
+            attempt,
+            latency,
+            status,
+        };
+        for observer in &self.observers {
+            observer.on_request(&event);
+        }
+        response
+    }
+
+    /// Send `request` to the node, retrying it when it is safe to
+    async fn rpc<T: DeserializeOwned>(&self, request: Value) -> Result<T, Error> {
+        let response = self.retry_policy.run(retry::is_idempotent(&request), |attempt| self.request(&request, attempt)).await?;
+        Ok(serde_json::from_value(response)?)
+    }
+
+    /// Returns how many RAW is owned and how many have not yet been received by `account`.
+    /// The response may be cached for a short while, see [with_cache](BananoApi::with_cache).
     pub async fn account_balance(&self, account: &Address) -> Result<AccountBalance, Error> {
         let request = json!({
             "action": "account_balance",
             "account": account.0,
         });
-        let account_balance: AccountBalance = self.client
-            .post(self.rpc_api.clone())
-            .json(&request)
-            .send().await?
-            .json().await?;
-        Ok(account_balance)
+        self.cached_rpc(request, |_| Caching::Volatile).await
     }
 
     /// Get number of blocks for a specific `account`
@@ -64,12 +239,7 @@ impl BananoApi {
             "action": "account_block_count",
             "account": account.0,
         });
-        let account_block: AccountBlockCount = self.client
-            .post(self.rpc_api.clone())
-            .json(&request)
-            .send().await?
-            .json().await?;
-        Ok(account_block)
+        self.rpc(request).await
     }
 
 	/// Returns frontier, open block, change representative block, balance, last modified timestamp from local database & block count for account.
@@ -81,19 +251,58 @@ impl BananoApi {
             "account": account.0,
 			"representative": true,
         });
-        let account_info: AccountInfo = self.client
-            .post(self.rpc_api.clone())
-            .json(&request)
-            .send().await?
-            .json().await?;
-        Ok(account_info)
+        self.rpc(request).await
+    }
+
+    /// Publish a signed `block` to the network and returns its hash.
+    ///
+    /// Rejections from the node are reported as [Error::ProcessError].
+    ///
+    /// Publishing is retried on network failures: a block is identified by its hash, so when a
+    /// retry is rejected as already known and the node has the block, the previous attempt went
+    /// through and this succeeds.
+    pub async fn process(&self, block: &StateBlock, options: ProcessOptions) -> Result<BlockHash, Error> {
+        let mut request = json!({
+            "action": "process",
+            "json_block": "true",
+            "block": block,
+        });
+        if let Some(subtype) = options.subtype {
+            request["subtype"] = json!(subtype);
+        }
+        if let Some(watch_work) = options.watch_work {
+            request["watch_work"] = json!(watch_work.to_string());
+        }
+        if options.is_async {
+            request["async"] = json!("true");
+        }
+        let request = &request;
+        let response = self.retry_policy.run(true, |attempt| async move {
+            match self.request(request, attempt).await {
+                Err(Error::NodeError(error)) if attempt > 0 && ProcessError::from(error.as_str()) == ProcessError::Old => {
+                    let hash = block.hash()?;
+                    match self.block_info(&hash).await {
+                        Ok(_) => Ok(json!({ "hash": hash })),
+                        Err(_) => Err(Error::NodeError(error)),
+                    }
+                }
+                response => response,
+            }
+        }).await.and_then(|response| Ok(serde_json::from_value::<ProcessResponse>(response)?));
+        match response {
+            Ok(ProcessResponse { hash: Some(hash) }) => Ok(hash),
+            // asynchronous processing only acknowledges the block
+            Ok(ProcessResponse { hash: None }) => block.hash(),
+            Err(Error::NodeError(error)) => Err(ProcessError::from(error.as_str()).into()),
+            Err(error) => Err(error),
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::Raw;
-    use crate::types::BlockHash;
+    use crate::types::sample_block;
     use crate::units::Banano;
     use super::*;
 	use chrono::*;
@@ -107,7 +316,7 @@ mod tests {
 
     #[test]
     fn account_balance() {
-        let banano = BananoApi::new("https://kaliumapi.appditto.com/api".into());
+        let banano = MockNode::new().api();
         let address = Address("ban_1hgtqu7cmgxb66ta4gxt7coimqcxp86nzi5b7u14ip9zzpqr16a3dbqdja1f".into());
         let account_balance = aw!(banano.account_balance(&address)).unwrap();
 
@@ -119,15 +328,16 @@ mod tests {
 
     #[test]
     fn account_block_count() {
-        let banano = BananoApi::new("https://kaliumapi.appditto.com/api".into());
+        let banano = MockNode::new().api();
         let address = Address("ban_1hgtqu7cmgxb66ta4gxt7coimqcxp86nzi5b7u14ip9zzpqr16a3dbqdja1f".into());
         let account_block = aw!(banano.account_block_count(&address)).unwrap();
         assert_eq!(4, account_block.block_count);
     }
 
 	#[test]
+    #[allow(deprecated)] // chrono 0.4.23 deprecated `ymd` and `date`
     fn account_info() {
-        let banano = BananoApi::new("https://kaliumapi.appditto.com/api".into());
+        let banano = MockNode::new().api();
         let address = Address("ban_1hgtqu7cmgxb66ta4gxt7coimqcxp86nzi5b7u14ip9zzpqr16a3dbqdja1f".into());
         let account_info = aw!(banano.account_info(&address)).unwrap();
 
@@ -139,4 +349,197 @@ mod tests {
 		assert_eq!(expected_representative_block, account_info.representative_block);
     }
 
+
+    #[test]
+    fn process_rejections() {
+        let banano = BananoApi::with_transport(MemoryTransport::new(|request| {
+            assert_eq!(request["action"], "process");
+            assert_eq!(request["subtype"], "change");
+            Ok(json!({"error": "Fork"}))
+        }));
+        let options = ProcessOptions {
+            subtype: Some(crate::types::BlockSubtype::Change),
+            ..Default::default()
+        };
+        match aw!(banano.process(&sample_block(), options)) {
+            Err(Error::ProcessError(ProcessError::Fork)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    fn quick_retries() -> RetryPolicy {
+        RetryPolicy {
+            initial_backoff: std::time::Duration::from_millis(1),
+            ..Default::default()
+        }
+    }
+
+    /// Transport failing the first `failures` requests, then answering with `handler`
+    fn flaky<F>(failures: usize, handler: F) -> (MemoryTransport, Arc<std::sync::atomic::AtomicUsize>)
+    where
+        F: Fn(&Value) -> Result<Value, Error> + Send + Sync + 'static,
+    {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let transport = MemoryTransport::new(move |request| {
+            if counter.fetch_add(1, Ordering::SeqCst) < failures {
+                Err(Error::Timeout)
+            } else {
+                handler(request)
+            }
+        });
+        (transport, calls)
+    }
+
+    #[test]
+    fn retries_idempotent_requests() {
+        let (transport, calls) = flaky(2, |_| Ok(json!({"count": "1000", "unchecked": "0", "cemented": "1000"})));
+        let banano = BananoApi::with_transport(transport).with_retry_policy(quick_retries());
+        assert_eq!(aw!(banano.block_count()).unwrap().count, 1000);
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 3);
+
+        let (transport, calls) = flaky(10, |_| unreachable!());
+        let banano = BananoApi::with_transport(transport).with_retry_policy(quick_retries());
+        assert!(matches!(aw!(banano.block_count()), Err(Error::Timeout)));
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 4);
+    }
+
+    #[test]
+    fn never_retries_unsafe_requests() {
+        let (transport, calls) = flaky(1, |_| Ok(json!({"account": "ban_1"})));
+        let banano = BananoApi::with_transport(transport).with_retry_policy(quick_retries());
+        let response: Result<Value, Error> = aw!(banano.rpc(json!({"action": "account_create", "wallet": "0"})));
+        assert!(matches!(response, Err(Error::Timeout)));
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+        // node errors are final
+        let (transport, calls) = flaky(0, |_| Ok(json!({"error": "Account not found"})));
+        let banano = BananoApi::with_transport(transport).with_retry_policy(quick_retries());
+        let address = Address("ban_1hgtqu7cmgxb66ta4gxt7coimqcxp86nzi5b7u14ip9zzpqr16a3dbqdja1f".into());
+        assert!(matches!(aw!(banano.account_info(&address)), Err(Error::NodeError(_))));
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn process_retry_is_idempotent() {
+        let block = sample_block();
+        let info = json!({
+            "block_account": block.account,
+            "balance": block.balance,
+            "height": "2",
+            "local_timestamp": "1624270400",
+            "successor": BlockHash::zero(),
+            "confirmed": "false",
+            "contents": block,
+        });
+
+        // the first attempt reached the node but its reply was lost
+        let (transport, _) = flaky(1, move |request| match request["action"].as_str() {
+            Some("block_info") => Ok(info.clone()),
+            _ => Ok(json!({"error": "Old block"})),
+        });
+        let banano = BananoApi::with_transport(transport).with_retry_policy(quick_retries());
+        assert_eq!(aw!(banano.process(&block, ProcessOptions::default())).unwrap(), block.hash().unwrap());
+
+        // an old block the node does not have is not the one previously sent
+        let (transport, _) = flaky(1, |request| match request["action"].as_str() {
+            Some("block_info") => Ok(json!({"error": "Block not found"})),
+            _ => Ok(json!({"error": "Old block"})),
+        });
+        let banano = BananoApi::with_transport(transport).with_retry_policy(quick_retries());
+        assert!(matches!(aw!(banano.process(&block, ProcessOptions::default())), Err(Error::ProcessError(ProcessError::Old))));
+
+        // without a retry, an old block is still a rejection
+        let (transport, _) = flaky(0, |_| Ok(json!({"error": "Old block"})));
+        let banano = BananoApi::with_transport(transport).with_retry_policy(quick_retries());
+        assert!(matches!(aw!(banano.process(&block, ProcessOptions::default())), Err(Error::ProcessError(ProcessError::Old))));
+    }
+
+    #[test]
+    fn observes_requests() {
+        use std::sync::Mutex;
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let log = events.clone();
+        let (transport, _) = flaky(1, |_| Ok(json!({"error": "Account not found"})));
+        let banano = BananoApi::with_transport(transport)
+            .with_retry_policy(quick_retries())
+            .with_observer(move |event: &RpcEvent| log.lock().unwrap().push((event.action.to_string(), event.attempt, event.status)));
+
+        let address = Address("ban_1hgtqu7cmgxb66ta4gxt7coimqcxp86nzi5b7u14ip9zzpqr16a3dbqdja1f".into());
+        assert!(aw!(banano.account_info(&address)).is_err());
+        assert_eq!(*events.lock().unwrap(), vec![
+            ("account_info".to_string(), 0, Err(ErrorClass::Timeout)),
+            ("account_info".to_string(), 1, Err(ErrorClass::Node)),
+        ]);
+    }
+
+    #[test]
+    fn caches_confirmed_blocks() {
+        use std::sync::Mutex;
+
+        let block = sample_block();
+        let confirmed = block.hash().unwrap();
+        let unconfirmed = BlockHash::from_str("40DB7EC1F71F7B3B66982007F20E687148BDB875E533121259C0BF69AEFE88D3").unwrap();
+        let hashes = [confirmed.clone(), unconfirmed.clone()];
+        let requested = Arc::new(Mutex::new(Vec::new()));
+        let log = requested.clone();
+        let banano = BananoApi::with_transport(MemoryTransport::new(move |request| {
+            let hashes: Vec<String> = serde_json::from_value(request["hashes"].clone()).unwrap();
+            log.lock().unwrap().extend(hashes.clone());
+            let blocks: serde_json::Map<String, Value> = hashes.into_iter().map(|hash| {
+                let is_confirmed = hash == confirmed.to_string();
+                (hash, json!({
+                    "block_account": block.account,
+                    "balance": block.balance,
+                    "height": "2",
+                    "local_timestamp": "1624270400",
+                    "successor": if is_confirmed { unconfirmed.to_string() } else { BlockHash::zero().to_string() },
+                    "confirmed": is_confirmed.to_string(),
+                    "contents": block,
+                }))
+            }).collect();
+            Ok(json!({ "blocks": blocks }))
+        })).with_cache(MemoryCache::new(100), std::time::Duration::ZERO);
+
+        assert_eq!(aw!(banano.blocks_info(&hashes)).unwrap().len(), 2);
+        assert_eq!(aw!(banano.blocks_info(&hashes)).unwrap().len(), 2);
+        assert!(aw!(banano.block_info(&hashes[0])).unwrap().confirmed);
+        // only the unconfirmed block is requested again
+        assert_eq!(*requested.lock().unwrap(), vec![hashes[0].to_string(), hashes[1].to_string(), hashes[1].to_string()]);
+    }
+
+    #[test]
+    fn ledger_stream_pages() {
+        use futures::TryStreamExt;
+
+        let mut addresses: Vec<Address> = vec![
+            "ban_3iwi45me3cgo9aza9wx5f7rder37hw11xtc1ek8psqxw5oxb8cujjad6qp9y",
+            "ban_3a9d1h6wt3zp8cqd6dhhgoyizmk1ciemqkrw97ysrphn7anm6xko1wxakaa1",
+            "ban_1dz36wby1azyjgh7t9nopjm3k5rduhmntercoz545my9s8nm7gcuthuq9fmq",
+            "ban_1fb7kaqaue49kf9w4mb9w3scuxipbdm3ez6ibnri4w8qexzg5f4r7on1dmxb",
+            "ban_3h9a64yqueuij1j9odt119r3ymm8n83wyyz7o9u7ram1tgfhsh1zqwjtzid9",
+        ].into_iter().map(|address| Address(address.into())).collect();
+        addresses.sort_by_key(|address| address.to_public_key().unwrap().0);
+
+        let ledger = addresses.clone();
+        let banano = BananoApi::with_transport(MemoryTransport::new(move |request| {
+            let count: usize = request["count"].as_str().unwrap().parse().unwrap();
+            let start = request.get("account").map(|account| ledger.iter().position(|a| a.0 == account.as_str().unwrap()).unwrap()).unwrap_or(0);
+            let accounts: serde_json::Map<String, Value> = ledger.iter().skip(start).take(count).map(|address| (address.0.clone(), json!({
+                "frontier": "40DB7EC1F71F7B3B66982007F20E687148BDB875E533121259C0BF69AEFE88D3",
+                "open_block": "40DB7EC1F71F7B3B66982007F20E687148BDB875E533121259C0BF69AEFE88D3",
+                "representative_block": "40DB7EC1F71F7B3B66982007F20E687148BDB875E533121259C0BF69AEFE88D3",
+                "balance": "1",
+                "modified_timestamp": "1624270400",
+                "block_count": "1",
+            }))).collect();
+            Ok(json!({ "accounts": accounts }))
+        }));
+
+        let streamed: Vec<Address> = aw!(banano.ledger_stream(LedgerOptions::default(), 2).map_ok(|(address, _)| address).try_collect()).unwrap();
+        assert_eq!(streamed, addresses);
+    }
+
 }
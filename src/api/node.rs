@@ -0,0 +1,365 @@
+use crate::{BananoApi, Error, Raw, encoding::deserialize_empty_as_default, types::{BlockHash, Difficulty}};
+use serde::Deserialize;
+use serde_json::json;
+use serde_with::{serde_as, DisplayFromStr};
+use std::{collections::HashMap, net::SocketAddr, time::Duration};
+
+/// Node version
+///
+/// [Nano documentation](https://docs.nano.org/commands/rpc-protocol/#version)
+#[serde_as]
+#[derive(Debug, Deserialize)]
+pub struct NodeVersion {
+    #[serde_as(as = "DisplayFromStr")]
+    pub rpc_version: u32,
+    #[serde_as(as = "DisplayFromStr")]
+    pub store_version: u32,
+    #[serde_as(as = "DisplayFromStr")]
+    pub protocol_version: u32,
+    pub node_vendor: String,
+    pub store_vendor: Option<String>,
+    pub network: Option<String>,
+    pub network_identifier: Option<BlockHash>,
+    pub build_info: Option<String>,
+}
+
+/// Number of blocks in the ledger of the node
+///
+/// [Nano documentation](https://docs.nano.org/commands/rpc-protocol/#block_count)
+#[serde_as]
+#[derive(Debug, Deserialize)]
+pub struct BlockCount {
+    #[serde_as(as = "DisplayFromStr")]
+    pub count: u64,
+    #[serde_as(as = "DisplayFromStr")]
+    pub unchecked: u64,
+    #[serde_as(as = "DisplayFromStr")]
+    pub cemented: u64,
+}
+
+#[serde_as]
+#[derive(Debug, Deserialize)]
+pub(crate) struct UptimeResponse {
+    #[serde_as(as = "DisplayFromStr")]
+    pub seconds: u64,
+}
+
+/// Peer of the node
+///
+/// [Nano documentation](https://docs.nano.org/commands/rpc-protocol/#peers)
+#[serde_as]
+#[derive(Debug, Deserialize)]
+pub struct Peer {
+    #[serde_as(as = "DisplayFromStr")]
+    pub protocol_version: u32,
+    pub node_id: Option<String>,
+    /// Transport used to talk to the peer, `tcp` or `udp`
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct PeersResponse {
+    #[serde(deserialize_with = "deserialize_empty_as_default")]
+    pub peers: HashMap<SocketAddr, Peer>,
+}
+
+/// Telemetry of a peer, or aggregated over all peers
+///
+/// [Nano documentation](https://docs.nano.org/commands/rpc-protocol/#telemetry)
+#[serde_as]
+#[derive(Debug, Clone, Deserialize)]
+pub struct Telemetry {
+    #[serde_as(as = "DisplayFromStr")]
+    pub block_count: u64,
+    #[serde_as(as = "DisplayFromStr")]
+    pub cemented_count: u64,
+    #[serde_as(as = "DisplayFromStr")]
+    pub unchecked_count: u64,
+    #[serde_as(as = "DisplayFromStr")]
+    pub account_count: u64,
+    #[serde_as(as = "DisplayFromStr")]
+    pub bandwidth_cap: u64,
+    #[serde_as(as = "DisplayFromStr")]
+    pub peer_count: u32,
+    #[serde_as(as = "DisplayFromStr")]
+    pub protocol_version: u32,
+    /// Uptime in seconds
+    #[serde_as(as = "DisplayFromStr")]
+    pub uptime: u64,
+    pub genesis_block: BlockHash,
+    #[serde_as(as = "DisplayFromStr")]
+    pub major_version: u32,
+    #[serde_as(as = "DisplayFromStr")]
+    pub minor_version: u32,
+    #[serde_as(as = "DisplayFromStr")]
+    pub patch_version: u32,
+    #[serde_as(as = "DisplayFromStr")]
+    pub pre_release_version: u32,
+    #[serde_as(as = "DisplayFromStr")]
+    pub maker: u32,
+    /// Milliseconds since the UNIX epoch
+    #[serde_as(as = "DisplayFromStr")]
+    pub timestamp: u64,
+    pub active_difficulty: Difficulty,
+    /// Only available for a single peer
+    pub node_id: Option<String>,
+    /// Only available for a single peer
+    pub signature: Option<String>,
+    /// Only available for a single peer
+    pub address: Option<String>,
+    /// Only available for a single peer
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default)]
+    pub port: Option<u16>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct TelemetryMetricsResponse {
+    #[serde(deserialize_with = "deserialize_empty_as_default")]
+    pub metrics: Vec<Telemetry>,
+}
+
+/// Difficulty of the work the network currently asks for
+///
+/// [Nano documentation](https://docs.nano.org/commands/rpc-protocol/#active_difficulty)
+#[serde_as]
+#[derive(Debug, Clone, Deserialize)]
+pub struct ActiveDifficulty {
+    pub network_minimum: Difficulty,
+    pub network_receive_minimum: Option<Difficulty>,
+    pub network_current: Difficulty,
+    pub network_receive_current: Option<Difficulty>,
+    #[serde_as(as = "DisplayFromStr")]
+    pub multiplier: f64,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct AvailableSupplyResponse {
+    pub available: Raw,
+}
+
+impl BananoApi {
+    /// Returns the version of the node and of its protocols
+    pub async fn version(&self) -> Result<NodeVersion, Error> {
+        let request = json!({
+            "action": "version",
+        });
+        self.rpc(request).await
+    }
+
+    /// Returns how many blocks are in the ledger, unchecked and cemented
+    pub async fn block_count(&self) -> Result<BlockCount, Error> {
+        let request = json!({
+            "action": "block_count",
+        });
+        self.rpc(request).await
+    }
+
+    /// Returns how long the node has been running
+    pub async fn uptime(&self) -> Result<Duration, Error> {
+        let request = json!({
+            "action": "uptime",
+        });
+        let response: UptimeResponse = self.rpc(request).await?;
+        Ok(Duration::from_secs(response.seconds))
+    }
+
+    /// Returns peers the node is connected to
+    pub async fn peers(&self) -> Result<HashMap<SocketAddr, Peer>, Error> {
+        let request = json!({
+            "action": "peers",
+            "peer_details": "true",
+        });
+        let response: PeersResponse = self.rpc(request).await?;
+        Ok(response.peers)
+    }
+
+    /// Returns telemetry aggregated over all peers of the node
+    pub async fn telemetry(&self) -> Result<Telemetry, Error> {
+        let request = json!({
+            "action": "telemetry",
+        });
+        self.rpc(request).await
+    }
+
+    /// Returns telemetry of each peer of the node
+    pub async fn telemetry_peers(&self) -> Result<Vec<Telemetry>, Error> {
+        let request = json!({
+            "action": "telemetry",
+            "raw": "true",
+        });
+        let response: TelemetryMetricsResponse = self.rpc(request).await?;
+        Ok(response.metrics)
+    }
+
+    /// Returns telemetry of the peer at `address`
+    pub async fn telemetry_peer(&self, address: &SocketAddr) -> Result<Telemetry, Error> {
+        let request = json!({
+            "action": "telemetry",
+            "address": address.ip().to_string(),
+            "port": address.port().to_string(),
+        });
+        self.rpc(request).await
+    }
+
+    /// Returns the work difficulty currently required by the network
+    pub async fn active_difficulty(&self) -> Result<ActiveDifficulty, Error> {
+        let request = json!({
+            "action": "active_difficulty",
+        });
+        self.rpc(request).await
+    }
+
+    /// Returns how much RAW is in circulation
+    pub async fn available_supply(&self) -> Result<Raw, Error> {
+        let request = json!({
+            "action": "available_supply",
+        });
+        let response: AvailableSupplyResponse = self.rpc(request).await?;
+        Ok(response.available)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_peers() {
+        let json = r#"{"peers": {
+            "[::ffff:172.17.0.1]:7071": {"protocol_version": "18", "node_id": "node_1y7j5rdqhg99uyab1145gu3yur1ax35a3b6qr417yt8cd6n86uiw3d4whty3", "type": "tcp"}
+        }}"#;
+        let response: PeersResponse = serde_json::from_str(json).unwrap();
+        let (address, peer) = response.peers.into_iter().next().unwrap();
+        assert_eq!(address.port(), 7071);
+        assert_eq!(peer.protocol_version, 18);
+        assert_eq!(peer.kind, Some("tcp".into()));
+
+        let response: PeersResponse = serde_json::from_str(r#"{"peers": ""}"#).unwrap();
+        assert!(response.peers.is_empty());
+    }
+
+    #[test]
+    fn deserialize_telemetry() {
+        let json = r#"{
+            "block_count": "5777903",
+            "cemented_count": "688819",
+            "unchecked_count": "443468",
+            "account_count": "620275",
+            "bandwidth_cap": "1572864",
+            "peer_count": "32",
+            "protocol_version": "18",
+            "uptime": "556896",
+            "genesis_block": "F61A79F286ABC5CC01D3D09686F0567812B889A5C63ADE0E82DD30F3B2D96463",
+            "major_version": "23",
+            "minor_version": "3",
+            "patch_version": "0",
+            "pre_release_version": "0",
+            "maker": "0",
+            "timestamp": "1587055945990",
+            "active_difficulty": "fffffe0000000000"
+        }"#;
+        let telemetry: Telemetry = serde_json::from_str(json).unwrap();
+        assert_eq!(telemetry.block_count, 5777903);
+        assert_eq!(telemetry.peer_count, 32);
+        assert_eq!(telemetry.major_version, 23);
+        assert_eq!(telemetry.timestamp, 1587055945990);
+        assert_eq!(telemetry.active_difficulty, Difficulty::DEFAULT);
+        assert_eq!(telemetry.node_id, None);
+        assert_eq!(telemetry.port, None);
+    }
+
+    #[test]
+    fn deserialize_raw_telemetry() {
+        let json = r#"{"metrics": [{
+            "block_count": "5777903",
+            "cemented_count": "688819",
+            "unchecked_count": "443468",
+            "account_count": "620275",
+            "bandwidth_cap": "1572864",
+            "peer_count": "32",
+            "protocol_version": "18",
+            "uptime": "556896",
+            "genesis_block": "F61A79F286ABC5CC01D3D09686F0567812B889A5C63ADE0E82DD30F3B2D96463",
+            "major_version": "23",
+            "minor_version": "3",
+            "patch_version": "0",
+            "pre_release_version": "0",
+            "maker": "0",
+            "timestamp": "1587055945990",
+            "active_difficulty": "fffffe0000000000",
+            "node_id": "node_1cmi8difuruopgzpnb4ybrnnj5rproxwuwe5mad7ucbsekakiwn37qqg1zo5",
+            "signature": "5F8DEE5F895D53E122FDEB4B1B4118A41F9DDB818C6B299B09DF59131AF9F201BB7057769423F6B0C868B57509177B54D5D2C731405FE607E1B4C6D1D79D9F01",
+            "address": "::ffff:152.89.106.89",
+            "port": "7071"
+        }]}"#;
+        let response: TelemetryMetricsResponse = serde_json::from_str(json).unwrap();
+        let telemetry = &response.metrics[0];
+        assert_eq!(telemetry.uptime, 556896);
+        assert_eq!(telemetry.node_id.as_deref(), Some("node_1cmi8difuruopgzpnb4ybrnnj5rproxwuwe5mad7ucbsekakiwn37qqg1zo5"));
+        assert_eq!(telemetry.address.as_deref(), Some("::ffff:152.89.106.89"));
+        assert_eq!(telemetry.port, Some(7071));
+
+        let response: TelemetryMetricsResponse = serde_json::from_str(r#"{"metrics": ""}"#).unwrap();
+        assert!(response.metrics.is_empty());
+    }
+
+    #[test]
+    fn deserialize_active_difficulty() {
+        let json = r#"{
+            "deprecated": "1",
+            "network_minimum": "fffffe0000000000",
+            "network_receive_minimum": "fffffe0000000000",
+            "network_current": "fffffe0000000000",
+            "network_receive_current": "fffffe0000000000",
+            "multiplier": "1"
+        }"#;
+        let difficulty: ActiveDifficulty = serde_json::from_str(json).unwrap();
+        assert_eq!(difficulty.network_minimum, Difficulty::DEFAULT);
+        assert_eq!(difficulty.network_receive_current, Some(Difficulty::DEFAULT));
+        assert_eq!(difficulty.multiplier, 1.0);
+
+        // older nodes have no receive difficulty
+        let json = r#"{"network_minimum": "fffffe0000000000", "network_current": "fffffe1000000000", "multiplier": "1.0078125"}"#;
+        let difficulty: ActiveDifficulty = serde_json::from_str(json).unwrap();
+        assert_eq!(difficulty.network_current, Difficulty(0xfffffe1000000000));
+        assert_eq!(difficulty.network_receive_minimum, None);
+        assert_eq!(difficulty.multiplier, 1.0078125);
+    }
+
+    #[test]
+    fn deserialize_version() {
+        let json = r#"{
+            "rpc_version": "1",
+            "store_version": "21",
+            "protocol_version": "19",
+            "node_vendor": "Banano V23.3",
+            "store_vendor": "LMDB 0.9.25",
+            "network": "live",
+            "network_identifier": "F61A79F286ABC5CC01D3D09686F0567812B889A5C63ADE0E82DD30F3B2D96463",
+            "build_info": "f8dbd73 \"GNU C++ version \" \"9.3.0\" \"BOOST 107300\" BUILT \"Mar 27 2022\""
+        }"#;
+        let version: NodeVersion = serde_json::from_str(json).unwrap();
+        assert_eq!(version.rpc_version, 1);
+        assert_eq!(version.protocol_version, 19);
+        assert_eq!(version.node_vendor, "Banano V23.3");
+        assert_eq!(version.network.as_deref(), Some("live"));
+        assert!(version.network_identifier.is_some());
+
+        // older nodes only report their versions
+        let json = r#"{"rpc_version": "1", "store_version": "14", "protocol_version": "17", "node_vendor": "Banano V17.0"}"#;
+        let version: NodeVersion = serde_json::from_str(json).unwrap();
+        assert_eq!(version.store_version, 14);
+        assert_eq!(version.network_identifier, None);
+    }
+
+    #[test]
+    fn deserialize_block_count() {
+        let json = r#"{"count": "1000", "unchecked": "10", "cemented": "25"}"#;
+        let block_count: BlockCount = serde_json::from_str(json).unwrap();
+        assert_eq!(block_count.count, 1000);
+        assert_eq!(block_count.unchecked, 10);
+        assert_eq!(block_count.cemented, 25);
+    }
+}
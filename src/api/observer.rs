@@ -0,0 +1,86 @@
+use crate::Error;
+use std::time::Duration;
+
+/// Kind of failure of a request, coarse enough to be used as a metric label
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorClass {
+    /// The endpoint could not be reached or the connection broke
+    Transport,
+    Timeout,
+    /// The endpoint asked to slow down
+    RateLimited,
+    /// The node answered with an error
+    Node,
+    /// The response could not be decoded
+    Decode,
+    /// No endpoint was available to send the request to
+    Unavailable,
+    Other,
+}
+
+impl From<&Error> for ErrorClass {
+    fn from(error: &Error) -> Self {
+        match error {
+            Error::RpcError(error) if error.is_timeout() => ErrorClass::Timeout,
+            Error::RpcError(error) if error.is_decode() => ErrorClass::Decode,
+            Error::RpcError(_) => ErrorClass::Transport,
+            Error::Timeout => ErrorClass::Timeout,
+            Error::RateLimited { .. } => ErrorClass::RateLimited,
+            Error::NodeError(_) | Error::ProcessError(_) => ErrorClass::Node,
+            Error::JsonError(_) => ErrorClass::Decode,
+            Error::NoEndpointAvailable => ErrorClass::Unavailable,
+            _ => ErrorClass::Other,
+        }
+    }
+}
+
+/// A request sent to a node, reported to observers once answered
+#[derive(Debug, Clone)]
+pub struct RpcEvent<'a> {
+    /// RPC action, like `account_info`
+    pub action: &'a str,
+    /// Endpoint which handled the request, when the transport tells
+    pub endpoint: Option<&'a str>,
+    /// 0 for the first attempt, then the number of the retry
+    pub attempt: u32,
+    pub latency: Duration,
+    pub status: Result<(), ErrorClass>,
+}
+
+/// Hook called for every request sent by [BananoApi](crate::BananoApi), to collect metrics
+///
+/// Observers are called from the request path, they should return quickly.
+///
+/// # Example:
+/// ```
+/// use banano_rs::{BananoApi, api::RpcEvent};
+///
+/// let banano = BananoApi::new("https://kaliumapi.appditto.com/api".into())
+///     .with_observer(|event: &RpcEvent| {
+///         if let Err(class) = event.status {
+///             eprintln!("{} failed after {:?}: {:?}", event.action, event.latency, class);
+///         }
+///     });
+/// ```
+pub trait RpcObserver: Send + Sync {
+    fn on_request(&self, event: &RpcEvent);
+}
+
+impl<F: Fn(&RpcEvent) + Send + Sync> RpcObserver for F {
+    fn on_request(&self, event: &RpcEvent) {
+        self(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_errors() {
+        assert_eq!(ErrorClass::from(&Error::NodeError("Account not found".into())), ErrorClass::Node);
+        assert_eq!(ErrorClass::from(&Error::RateLimited { retry_after: None }), ErrorClass::RateLimited);
+        assert_eq!(ErrorClass::from(&Error::NoEndpointAvailable), ErrorClass::Unavailable);
+        assert_eq!(ErrorClass::from(&Error::InvalidAddress), ErrorClass::Other);
+    }
+}
@@ -0,0 +1,183 @@
+use crate::Error;
+use super::Transport;
+use async_trait::async_trait;
+use log::warn;
+use serde_json::Value;
+use std::{sync::Mutex, time::{Duration, Instant}};
+
+/// Slowest rate a limiter accepts, one request per hour
+const MIN_REQUESTS_PER_SECOND: f64 = 1.0 / 3600.0;
+
+/// How many requests may be sent to an endpoint
+#[derive(Debug, Clone)]
+pub struct RateLimit {
+    /// Sustained rate of requests, at least one per hour
+    pub requests_per_second: f64,
+    /// How many requests may be sent at once after a quiet period
+    pub burst: u32,
+}
+
+impl RateLimit {
+    /// Rates below one request per hour, including zero, negative and NaN ones, are raised to it
+    pub fn new(requests_per_second: f64, burst: u32) -> Self {
+        RateLimit {
+            requests_per_second: requests_per_second.max(MIN_REQUESTS_PER_SECOND),
+            burst,
+        }
+    }
+}
+
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+    /// Set when the endpoint answered with HTTP 429
+    paused_until: Option<Instant>,
+}
+
+/// Transport throttling requests sent through another transport with a token bucket.
+///
+/// Requests wait for a token before being sent, tokens being refilled at `requests_per_second`
+/// up to `burst`. When the endpoint answers with [Error::RateLimited], no request is sent until
+/// its `Retry-After` delay has passed.
+///
+/// Wrap each endpoint in its own limiter to limit them separately, for instance with
+/// [FailoverTransport::with_rate_limit](super::FailoverTransport::with_rate_limit).
+///
+/// # Example:
+/// ```
+/// use banano_rs::{BananoApi, api::{HttpTransport, RateLimit, RateLimitedTransport}};
+///
+/// let transport = HttpTransport::new("https://kaliumapi.appditto.com/api".into());
+/// let banano = BananoApi::with_transport(RateLimitedTransport::new(transport, RateLimit::new(5.0, 10)));
+/// ```
+pub struct RateLimitedTransport<T> {
+    transport: T,
+    rate_limit: RateLimit,
+    bucket: Mutex<Bucket>,
+}
+
+impl<T: Transport> RateLimitedTransport<T> {
+    /// Throttle requests sent through `transport` according to `rate_limit`
+    pub fn new(transport: T, rate_limit: RateLimit) -> Self {
+        let burst = rate_limit.burst.max(1);
+        let requests_per_second = rate_limit.requests_per_second.max(MIN_REQUESTS_PER_SECOND);
+        RateLimitedTransport {
+            transport,
+            rate_limit: RateLimit { requests_per_second, burst },
+            bucket: Mutex::new(Bucket {
+                tokens: burst as f64,
+                refilled_at: Instant::now(),
+                paused_until: None,
+            }),
+        }
+    }
+
+    /// Take a token, or returns how long to wait before trying again
+    fn try_acquire(&self) -> Result<(), Duration> {
+        let mut bucket = self.bucket.lock().unwrap();
+        let now = Instant::now();
+        if let Some(paused_until) = bucket.paused_until {
+            if paused_until > now {
+                return Err(paused_until - now);
+            }
+            bucket.paused_until = None;
+        }
+
+        let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.rate_limit.requests_per_second).min(self.rate_limit.burst as f64);
+        bucket.refilled_at = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / self.rate_limit.requests_per_second))
+        }
+    }
+
+    async fn acquire(&self) {
+        while let Err(wait) = self.try_acquire() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    fn pause(&self, duration: Duration) {
+        let mut bucket = self.bucket.lock().unwrap();
+        let until = Instant::now() + duration;
+        if bucket.paused_until.map_or(true, |paused_until| paused_until < until) {
+            bucket.paused_until = Some(until);
+        }
+        bucket.tokens = 0.0;
+    }
+}
+
+#[async_trait]
+impl<T: Transport> Transport for RateLimitedTransport<T> {
+    async fn request(&self, request: &Value) -> Result<Value, Error> {
+        self.request_with_endpoint(request).await.0
+    }
+
+    fn endpoint(&self) -> Option<String> {
+        self.transport.endpoint()
+    }
+
+    async fn request_with_endpoint(&self, request: &Value) -> (Result<Value, Error>, Option<String>) {
+        self.acquire().await;
+        let (response, endpoint) = self.transport.request_with_endpoint(request).await;
+        if let Err(Error::RateLimited { retry_after }) = &response {
+            let pause = retry_after.unwrap_or_else(|| Duration::from_secs_f64(1.0 / self.rate_limit.requests_per_second));
+            warn!("Rate limited by RPC endpoint {}, pausing requests for {:?}", endpoint.as_deref().unwrap_or("?"), pause);
+            self.pause(pause);
+        }
+        (response, endpoint)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::MemoryTransport;
+    use serde_json::json;
+    use std::sync::{Arc, atomic::{AtomicUsize, Ordering}};
+
+    #[test]
+    fn throttles_after_burst() {
+        let transport = RateLimitedTransport::new(MemoryTransport::new(|_| Ok(json!({}))), RateLimit::new(20.0, 2));
+        let request = json!({"action": "version"});
+        let start = Instant::now();
+        for _ in 0..4 {
+            tokio_test::block_on(transport.request(&request)).unwrap();
+        }
+        // the burst goes through at once, the two next requests wait for 50ms each
+        assert!(start.elapsed() >= Duration::from_millis(95));
+    }
+
+    #[test]
+    fn pauses_when_rate_limited() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let transport = RateLimitedTransport::new(MemoryTransport::new(move |_| {
+            match counter.fetch_add(1, Ordering::SeqCst) {
+                0 => Err(Error::RateLimited { retry_after: Some(Duration::from_millis(100)) }),
+                _ => Ok(json!({})),
+            }
+        }), RateLimit::new(1000.0, 10));
+        let request = json!({"action": "version"});
+
+        assert!(matches!(tokio_test::block_on(transport.request(&request)), Err(Error::RateLimited { .. })));
+        let start = Instant::now();
+        tokio_test::block_on(transport.request(&request)).unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(95));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn raises_invalid_rates() {
+        assert_eq!(RateLimit::new(0.0, 1).requests_per_second, MIN_REQUESTS_PER_SECOND);
+        assert_eq!(RateLimit::new(f64::NAN, 1).requests_per_second, MIN_REQUESTS_PER_SECOND);
+
+        let rate_limit = RateLimit { requests_per_second: -1.0, burst: 1 };
+        let transport = RateLimitedTransport::new(MemoryTransport::new(|_| Err(Error::RateLimited { retry_after: None })), rate_limit);
+        let response = tokio_test::block_on(transport.request(&json!({"action": "version"})));
+        assert!(matches!(response, Err(Error::RateLimited { .. })));
+    }
+}
@@ -0,0 +1,134 @@
+use crate::{Address, BananoApi, Error, Raw, types::BlockHash};
+use serde::{Deserialize, Deserializer};
+use serde_json::json;
+use indexmap::IndexMap;
+
+/// Options for [receivable](crate::BananoApi::receivable)
+///
+/// [Nano documentation](https://docs.nano.org/commands/rpc-protocol/#receivable)
+#[derive(Debug, Clone, Default)]
+pub struct ReceivableOptions {
+    /// Maximum number of blocks to return
+    pub count: Option<u64>,
+    /// Ignore blocks below this amount. Defaults to 1 raw, which does not filter anything out
+    /// but makes the node return amounts.
+    pub threshold: Option<Raw>,
+    /// Also return the account which sent each block
+    pub source: bool,
+    /// Only return blocks whose send is confirmed, `true` by default on the node
+    pub include_only_confirmed: Option<bool>,
+    /// Sort blocks by decreasing amount
+    pub sorting: bool,
+}
+
+/// Receivable block
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Receivable {
+    pub amount: Raw,
+    /// Only available when `source` is set in the options
+    pub source: Option<Address>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ReceivableResponse {
+    #[serde(deserialize_with = "deserialize_receivable_blocks")]
+    pub blocks: IndexMap<BlockHash, Receivable>,
+}
+
+fn deserialize_receivable_blocks<'de, D>(deserializer: D) -> Result<IndexMap<BlockHash, Receivable>, <D as Deserializer<'de>>::Error>
+where
+    D: Deserializer<'de>,
+{
+    // depending on options, the node returns amounts or amounts with sources, and an empty string
+    // when there is nothing to receive
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Blocks {
+        Empty(String),
+        Amounts(IndexMap<BlockHash, Raw>),
+        Sources(IndexMap<BlockHash, Receivable>),
+    }
+
+    Ok(match Blocks::deserialize(deserializer)? {
+        Blocks::Empty(s) if s.is_empty() => IndexMap::new(),
+        Blocks::Empty(s) => return Err(serde::de::Error::custom(format!("unexpected blocks: {}", s))),
+        Blocks::Amounts(amounts) => amounts
+            .into_iter()
+            .map(|(hash, amount)| (hash, Receivable { amount, source: None }))
+            .collect(),
+        Blocks::Sources(blocks) => blocks,
+    })
+}
+
+impl BananoApi {
+    /// Returns blocks sent to `account` which have not been received yet, in the order of the node:
+    /// by decreasing amount when [sorting](ReceivableOptions::sorting) is set
+    pub async fn receivable(&self, account: &Address, options: ReceivableOptions) -> Result<IndexMap<BlockHash, Receivable>, Error> {
+        self.receivable_action("receivable", account, options).await
+    }
+
+    /// Same as [receivable](BananoApi::receivable), using the `pending` action understood by nodes
+    /// older than V23
+    pub async fn pending(&self, account: &Address, options: ReceivableOptions) -> Result<IndexMap<BlockHash, Receivable>, Error> {
+        self.receivable_action("pending", account, options).await
+    }
+
+    async fn receivable_action(&self, action: &str, account: &Address, options: ReceivableOptions) -> Result<IndexMap<BlockHash, Receivable>, Error> {
+        let mut request = json!({
+            "action": action,
+            "account": account,
+            "threshold": options.threshold.filter(|threshold| *threshold > 0u128).unwrap_or_else(|| Raw::new(1u128)),
+            "source": options.source.to_string(),
+            "sorting": options.sorting.to_string(),
+        });
+        if let Some(count) = options.count {
+            request["count"] = json!(count.to_string());
+        }
+        if let Some(include_only_confirmed) = options.include_only_confirmed {
+            request["include_only_confirmed"] = json!(include_only_confirmed.to_string());
+        }
+        let response: ReceivableResponse = self.rpc(request).await?;
+        Ok(response.blocks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn deserialize_receivable_blocks() {
+        let hash = BlockHash::from_str("000D1BAEC8EC208142C99059B393051BAC8380F9B5A2E6B2489A277D81789F3F").unwrap();
+
+        let amounts = r#"{"blocks": {"000D1BAEC8EC208142C99059B393051BAC8380F9B5A2E6B2489A277D81789F3F": "6000000000000000000000000000000"}}"#;
+        let response: ReceivableResponse = serde_json::from_str(amounts).unwrap();
+        assert_eq!(response.blocks[&hash], Receivable { amount: Raw::new(6000000000000000000000000000000u128), source: None });
+
+        let sources = r#"{"blocks": {"000D1BAEC8EC208142C99059B393051BAC8380F9B5A2E6B2489A277D81789F3F": {
+            "amount": "6000000000000000000000000000000",
+            "source": "ban_3t6k35gi95xu6tergt6p69ck76ogmitsa8mnijtpxm9fkcm736xtoncuohr3"
+        }}}"#;
+        let response: ReceivableResponse = serde_json::from_str(sources).unwrap();
+        assert_eq!(
+            response.blocks[&hash].source,
+            Some(Address("ban_3t6k35gi95xu6tergt6p69ck76ogmitsa8mnijtpxm9fkcm736xtoncuohr3".into()))
+        );
+
+        let empty: ReceivableResponse = serde_json::from_str(r#"{"blocks": ""}"#).unwrap();
+        assert!(empty.blocks.is_empty());
+    }
+
+    #[tokio::test]
+    async fn keeps_the_order_of_the_node() {
+        let node = crate::api::MockNode::new();
+        let account = Address("ban_3t6k35gi95xu6tergt6p69ck76ogmitsa8mnijtpxm9fkcm736xtoncuohr3".into());
+        for amount in &[3u128, 7, 1, 9, 5] {
+            node.add_receivable(&account, &Address(crate::api::MockNode::ACCOUNT.into()), Raw::new(*amount)).unwrap();
+        }
+        let options = ReceivableOptions { sorting: true, ..Default::default() };
+        let blocks = node.api().receivable(&account, options).await.unwrap();
+        let amounts: Vec<u128> = blocks.values().map(|block| block.amount.to_u128()).collect();
+        assert_eq!(amounts, vec![9, 7, 5, 3, 1]);
+    }
+}
@@ -0,0 +1,167 @@
+use crate::{Address, BananoApi, Error, Raw, encoding::deserialize_empty_as_default};
+use serde::Deserialize;
+use serde_json::json;
+use indexmap::IndexMap;
+use std::collections::HashMap;
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct RepresentativesResponse {
+    #[serde(deserialize_with = "deserialize_empty_as_default")]
+    pub representatives: IndexMap<Address, Raw>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct RepresentativesOnlineResponse {
+    #[serde(deserialize_with = "deserialize_empty_as_default")]
+    pub representatives: HashMap<Address, RepresentativeWeight>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct RepresentativeWeight {
+    pub weight: Raw,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct DelegatorsResponse {
+    #[serde(deserialize_with = "deserialize_empty_as_default")]
+    pub delegators: HashMap<Address, Raw>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct DelegatorsCountResponse {
+    #[serde(with = "serde_with::rust::display_fromstr")]
+    pub count: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct AccountWeightResponse {
+    pub weight: Raw,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct AccountRepresentativeResponse {
+    pub representative: Address,
+}
+
+/// Confirmation quorum
+///
+/// [Nano documentation](https://docs.nano.org/commands/rpc-protocol/#confirmation_quorum)
+#[derive(Debug, Deserialize)]
+pub struct ConfirmationQuorum {
+    /// Voting weight needed to confirm a block
+    pub quorum_delta: Raw,
+    #[serde(with = "serde_with::rust::display_fromstr")]
+    pub online_weight_quorum_percent: u8,
+    pub online_weight_minimum: Raw,
+    pub online_stake_total: Raw,
+    pub peers_stake_total: Raw,
+    pub trended_stake_total: Option<Raw>,
+}
+
+impl BananoApi {
+    /// Returns representatives with their voting weight, sorted by decreasing weight when `sorting` is set
+    pub async fn representatives(&self, count: Option<u64>, sorting: bool) -> Result<IndexMap<Address, Raw>, Error> {
+        let mut request = json!({
+            "action": "representatives",
+            "sorting": sorting.to_string(),
+        });
+        if let Some(count) = count {
+            request["count"] = json!(count.to_string());
+        }
+        let response: RepresentativesResponse = self.rpc(request).await?;
+        Ok(response.representatives)
+    }
+
+    /// Returns representatives which recently voted, with their voting weight
+    pub async fn representatives_online(&self) -> Result<HashMap<Address, Raw>, Error> {
+        let request = json!({
+            "action": "representatives_online",
+            "weight": "true",
+        });
+        let response: RepresentativesOnlineResponse = self.rpc(request).await?;
+        Ok(response.representatives
+            .into_iter()
+            .map(|(representative, weight)| (representative, weight.weight))
+            .collect())
+    }
+
+    /// Returns accounts delegating their voting weight to `representative`, with their balance
+    pub async fn delegators(&self, representative: &Address) -> Result<HashMap<Address, Raw>, Error> {
+        let request = json!({
+            "action": "delegators",
+            "account": representative,
+        });
+        let response: DelegatorsResponse = self.rpc(request).await?;
+        Ok(response.delegators)
+    }
+
+    /// Returns the number of accounts delegating their voting weight to `representative`
+    pub async fn delegators_count(&self, representative: &Address) -> Result<u64, Error> {
+        let request = json!({
+            "action": "delegators_count",
+            "account": representative,
+        });
+        let response: DelegatorsCountResponse = self.rpc(request).await?;
+        Ok(response.count)
+    }
+
+    /// Returns the voting weight delegated to `account`
+    pub async fn account_weight(&self, account: &Address) -> Result<Raw, Error> {
+        let request = json!({
+            "action": "account_weight",
+            "account": account,
+        });
+        let response: AccountWeightResponse = self.rpc(request).await?;
+        Ok(response.weight)
+    }
+
+    /// Returns the representative of `account`
+    pub async fn account_representative(&self, account: &Address) -> Result<Address, Error> {
+        let request = json!({
+            "action": "account_representative",
+            "account": account,
+        });
+        let response: AccountRepresentativeResponse = self.rpc(request).await?;
+        Ok(response.representative)
+    }
+
+    /// Returns voting weight figures used by the node to confirm blocks
+    pub async fn confirmation_quorum(&self) -> Result<ConfirmationQuorum, Error> {
+        let request = json!({
+            "action": "confirmation_quorum",
+        });
+        self.rpc(request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_representatives_online() {
+        let json = r#"{"representatives": {
+            "ban_1fomoz167m7o38gw4rzt7hz67oq6itejpt4yocrfywujbpatd711cjew8gjj": {"weight": "150462654614686936429917024683496890"}
+        }}"#;
+        let response: RepresentativesOnlineResponse = serde_json::from_str(json).unwrap();
+        let representative = Address("ban_1fomoz167m7o38gw4rzt7hz67oq6itejpt4yocrfywujbpatd711cjew8gjj".into());
+        assert_eq!(response.representatives[&representative].weight, Raw::new(150462654614686936429917024683496890u128));
+
+        let response: DelegatorsResponse = serde_json::from_str(r#"{"delegators": ""}"#).unwrap();
+        assert!(response.delegators.is_empty());
+    }
+
+    #[tokio::test]
+    async fn keeps_the_order_of_the_node() {
+        use crate::{api::MockNode, types::{Account, PrivateKey, Seed}};
+
+        let node = MockNode::empty();
+        for (index, weight) in [3u128, 7, 1, 9, 5].iter().enumerate() {
+            let representative = Account::from(PrivateKey::from_seed(Seed([index as u8 + 1; 32]), 0)).address;
+            node.add_account(&representative, Raw::new(*weight), &representative).unwrap();
+        }
+        let representatives = node.api().representatives(None, true).await.unwrap();
+        let weights: Vec<u128> = representatives.values().map(|weight| weight.to_u128()).collect();
+        assert_eq!(weights, vec![9, 7, 5, 3, 1]);
+    }
+}
@@ -0,0 +1,183 @@
+use crate::Error;
+use log::debug;
+use rand::Rng;
+use serde_json::Value;
+use std::{future::Future, time::Duration};
+
+/// Actions which can be sent again without side effects
+const IDEMPOTENT_ACTIONS: &[&str] = &[
+    "account_balance",
+    "account_block_count",
+    "account_history",
+    "account_info",
+    "account_representative",
+    "account_weight",
+    "active_difficulty",
+    "available_supply",
+    "block_confirm",
+    "block_count",
+    "block_info",
+    "blocks_info",
+    "chain",
+    "confirmation_active",
+    "confirmation_history",
+    "confirmation_info",
+    "confirmation_quorum",
+    "delegators",
+    "delegators_count",
+    "frontier_count",
+    "frontiers",
+    "ledger",
+    "peers",
+    "pending",
+    "receivable",
+    "representatives",
+    "representatives_online",
+    "successors",
+    "telemetry",
+    "uptime",
+    "version",
+    "wallet_balances",
+    "wallet_representative_set",
+    "work_cancel",
+    "work_generate",
+    "work_validate",
+];
+
+/// Whether `request` can be retried blindly. `process` is handled on its own, as a block can only
+/// be published once, and `send` is only safe with an `id`.
+pub(crate) fn is_idempotent(request: &Value) -> bool {
+    match request["action"].as_str() {
+        Some("send") => request.get("id").is_some(),
+        Some(action) => IDEMPOTENT_ACTIONS.contains(&action),
+        None => false,
+    }
+}
+
+/// Whether the request may succeed if sent again. Rate limited requests were not handled and can
+/// always be sent again, other transport failures only for `idempotent` requests. Replies from the
+/// node are final.
+pub(crate) fn is_retryable(error: &Error, idempotent: bool) -> bool {
+    match error {
+        Error::RateLimited { .. } => true,
+        Error::RpcError(_) | Error::Timeout | Error::NoEndpointAvailable => idempotent,
+        _ => false,
+    }
+}
+
+/// How requests failing because of the network are retried
+///
+/// Delays grow exponentially from `initial_backoff` up to `max_backoff`, with a random jitter
+/// so that many clients do not retry all at once.
+///
+/// # Example:
+/// ```
+/// use banano_rs::{BananoApi, api::RetryPolicy};
+/// use std::time::Duration;
+///
+/// let banano = BananoApi::new("https://kaliumapi.appditto.com/api".into())
+///     .with_retry_policy(RetryPolicy {
+///         max_retries: 5,
+///         initial_backoff: Duration::from_millis(100),
+///         ..Default::default()
+///     });
+/// ```
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// How many times a request is sent again after the first attempt
+    pub max_retries: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    /// Randomly shorten delays by up to half
+    pub jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(5),
+            jitter: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Never retry
+    pub fn none() -> Self {
+        RetryPolicy {
+            max_retries: 0,
+            ..Default::default()
+        }
+    }
+
+    /// Delay before retry number `attempt` (starting at 0)
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let backoff = self.initial_backoff
+            .checked_mul(2u32.saturating_pow(attempt))
+            .unwrap_or(self.max_backoff)
+            .min(self.max_backoff);
+        if self.jitter {
+            backoff.mul_f64(rand::thread_rng().gen_range(0.5..=1.0))
+        } else {
+            backoff
+        }
+    }
+
+    /// Run `operation` until it succeeds, fails with an error which is not worth retrying, or
+    /// retries are exhausted. `operation` is given the attempt number, starting at 0.
+    pub(crate) async fn run<T, F, Fut>(&self, idempotent: bool, mut operation: F) -> Result<T, Error>
+    where
+        F: FnMut(u32) -> Fut,
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        let mut attempt = 0;
+        loop {
+            match operation(attempt).await {
+                Err(error) if attempt < self.max_retries && is_retryable(&error, idempotent) => {
+                    let backoff = match error {
+                        Error::RateLimited { retry_after: Some(retry_after) } => retry_after.max(self.backoff(attempt)),
+                        _ => self.backoff(attempt),
+                    };
+                    debug!("Retrying in {:?} after error: {}", backoff, error);
+                    tokio::time::sleep(backoff).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn idempotency() {
+        assert!(is_idempotent(&json!({"action": "account_info"})));
+        assert!(!is_idempotent(&json!({"action": "process"})));
+        assert!(!is_idempotent(&json!({"action": "send"})));
+        assert!(is_idempotent(&json!({"action": "send", "id": "payout-42"})));
+        assert!(!is_idempotent(&json!({"action": "receive"})));
+
+        assert!(is_retryable(&Error::Timeout, true));
+        assert!(!is_retryable(&Error::Timeout, false));
+        assert!(is_retryable(&Error::RateLimited { retry_after: None }, false));
+        assert!(!is_retryable(&Error::NodeError("Account not found".into()), true));
+    }
+
+    #[test]
+    fn exponential_backoff() {
+        let policy = RetryPolicy {
+            jitter: false,
+            ..Default::default()
+        };
+        assert_eq!(policy.backoff(0), Duration::from_millis(200));
+        assert_eq!(policy.backoff(2), Duration::from_millis(800));
+        assert_eq!(policy.backoff(10), Duration::from_secs(5));
+        assert_eq!(policy.backoff(100), Duration::from_secs(5));
+    }
+}
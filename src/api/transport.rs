@@ -0,0 +1,188 @@
+use crate::Error;
+use async_trait::async_trait;
+use reqwest::{Client, StatusCode, header::RETRY_AFTER};
+use serde_json::Value;
+use std::{sync::Arc, time::Duration};
+
+/// Carries JSON RPC requests to a node and brings back its responses.
+///
+/// [BananoApi](crate::BananoApi) talks HTTP through [HttpTransport] by default. Implement this trait to
+/// reach a node some other way (IPC, unix sockets...), or to wrap another transport with your own
+/// middleware.
+///
+/// # Example:
+/// ```
+/// use banano_rs::{BananoApi, Error, api::{HttpTransport, Transport}};
+/// use async_trait::async_trait;
+/// use serde_json::Value;
+///
+/// struct Logged(HttpTransport);
+///
+/// #[async_trait]
+/// impl Transport for Logged {
+///     async fn request(&self, request: &Value) -> Result<Value, Error> {
+///         println!("-> {}", request);
+///         self.0.request(request).await
+///     }
+/// }
+///
+/// let banano = BananoApi::with_transport(Logged(HttpTransport::new("https://kaliumapi.appditto.com/api".into())));
+/// ```
+#[async_trait]
+pub trait Transport: Send + Sync {
+    /// Send `request` and returns the JSON response of the node, even if it is an error reply
+    async fn request(&self, request: &Value) -> Result<Value, Error>;
+
+    /// Name of the endpoint requests are sent to, if there is a single one
+    fn endpoint(&self) -> Option<String> {
+        None
+    }
+
+    /// Same as [request](Transport::request), also returning the name of the endpoint which
+    /// handled the request. Transports choosing between several endpoints should override this.
+    async fn request_with_endpoint(&self, request: &Value) -> (Result<Value, Error>, Option<String>) {
+        (self.request(request).await, self.endpoint())
+    }
+}
+
+#[async_trait]
+impl<T: Transport + ?Sized> Transport for Arc<T> {
+    async fn request(&self, request: &Value) -> Result<Value, Error> {
+        (**self).request(request).await
+    }
+
+    fn endpoint(&self) -> Option<String> {
+        (**self).endpoint()
+    }
+
+    async fn request_with_endpoint(&self, request: &Value) -> (Result<Value, Error>, Option<String>) {
+        (**self).request_with_endpoint(request).await
+    }
+}
+
+#[async_trait]
+impl<T: Transport + ?Sized> Transport for Box<T> {
+    async fn request(&self, request: &Value) -> Result<Value, Error> {
+        (**self).request(request).await
+    }
+
+    fn endpoint(&self) -> Option<String> {
+        (**self).endpoint()
+    }
+
+    async fn request_with_endpoint(&self, request: &Value) -> (Result<Value, Error>, Option<String>) {
+        (**self).request_with_endpoint(request).await
+    }
+}
+
+/// Transport posting requests to the HTTP RPC API of a node
+///
+/// An HTTP 429 status is reported as [Error::RateLimited], along with the `Retry-After` delay.
+#[derive(Debug, Clone)]
+pub struct HttpTransport {
+    url: String,
+    client: Client,
+}
+
+impl HttpTransport {
+    /// Transport to the RPC API at `url`
+    pub fn new(url: String) -> Self {
+        Self::with_client(url, Client::new())
+    }
+
+    /// Transport to the RPC API at `url`, using an already configured `client`
+    pub fn with_client(url: String, client: Client) -> Self {
+        HttpTransport { url, client }
+    }
+
+    /// URL of the RPC API
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+}
+
+#[async_trait]
+impl Transport for HttpTransport {
+    async fn request(&self, request: &Value) -> Result<Value, Error> {
+        let response = self.client
+            .post(self.url.clone())
+            .json(request)
+            .send().await?;
+        if response.status() == StatusCode::TOO_MANY_REQUESTS {
+            let retry_after = response.headers()
+                .get(RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(parse_retry_after);
+            return Err(Error::RateLimited { retry_after });
+        }
+        Ok(response.json().await?)
+    }
+
+    fn endpoint(&self) -> Option<String> {
+        Some(self.url.clone())
+    }
+}
+
+/// Parse a `Retry-After` header, either a number of seconds or an HTTP date
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    if let Ok(seconds) = value.trim().parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value.trim()).ok()?;
+    (date.with_timezone(&chrono::Utc) - chrono::Utc::now()).to_std().ok().or(Some(Duration::ZERO))
+}
+
+type Handler = dyn Fn(&Value) -> Result<Value, Error> + Send + Sync;
+
+/// Transport answering requests in memory, mostly useful to fake a node in tests
+///
+/// # Example:
+/// ```
+/// use banano_rs::{BananoApi, api::MemoryTransport};
+/// use serde_json::json;
+///
+/// let banano = BananoApi::with_transport(MemoryTransport::new(|request| {
+///     match request["action"].as_str() {
+///         Some("block_count") => Ok(json!({"count": "1000", "unchecked": "0", "cemented": "1000"})),
+///         _ => Ok(json!({"error": "Unknown command"})),
+///     }
+/// }));
+/// let block_count = tokio_test::block_on(banano.block_count()).unwrap();
+/// assert_eq!(block_count.count, 1000);
+/// ```
+pub struct MemoryTransport {
+    handler: Box<Handler>,
+}
+
+impl MemoryTransport {
+    /// Answer each request with `handler`
+    pub fn new<F>(handler: F) -> Self
+    where
+        F: Fn(&Value) -> Result<Value, Error> + Send + Sync + 'static,
+    {
+        MemoryTransport {
+            handler: Box::new(handler),
+        }
+    }
+}
+
+#[async_trait]
+impl Transport for MemoryTransport {
+    async fn request(&self, request: &Value) -> Result<Value, Error> {
+        (self.handler)(request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retry_after() {
+        assert_eq!(parse_retry_after("120"), Some(Duration::from_secs(120)));
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), Some(Duration::ZERO));
+        let later = (chrono::Utc::now() + chrono::Duration::seconds(60)).to_rfc2822();
+        assert!(parse_retry_after(&later).unwrap() > Duration::from_secs(55));
+        assert_eq!(parse_retry_after("soon"), None);
+    }
+}
@@ -0,0 +1,225 @@
+//! Wallet RPCs, operating on wallets stored by the node itself.
+//!
+//! These are only available on nodes with `enable_control` set, which public nodes never do,
+//! so they are kept apart from [BananoApi] behind the `node-wallet` feature.
+
+use crate::{Address, BananoApi, Error, Raw, encoding::{deserialize_bool, deserialize_empty_as_default, to_hex}, types::{BlockHash, PrivateKey, Seed, WalletId}};
+use super::AccountBalance;
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::HashMap;
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct WalletCreateResponse {
+    pub wallet: WalletId,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct AccountResponse {
+    pub account: Address,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct AccountsResponse {
+    #[serde(deserialize_with = "deserialize_empty_as_default")]
+    pub accounts: Vec<Address>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct BlockResponse {
+    pub block: BlockHash,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct WalletBalancesResponse {
+    #[serde(deserialize_with = "deserialize_empty_as_default")]
+    pub balances: HashMap<Address, AccountBalance>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct WalletRepresentativeSetResponse {
+    #[serde(deserialize_with = "deserialize_bool")]
+    pub set: bool,
+}
+
+/// Wallet RPCs of a node, see [BananoApi::node_wallet]
+pub struct NodeWalletApi<'a> {
+    api: &'a BananoApi,
+}
+
+impl BananoApi {
+    /// Access the wallet RPCs of the node
+    ///
+    /// # Example:
+    /// ```no_run
+    /// # async fn run() -> banano_rs::Result<()> {
+    /// let banano = banano_rs::BananoApi::new("http://localhost:7072".into());
+    /// let wallets = banano.node_wallet();
+    /// let wallet = wallets.wallet_create(None).await?;
+    /// let account = wallets.account_create(&wallet, None).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn node_wallet(&self) -> NodeWalletApi<'_> {
+        NodeWalletApi { api: self }
+    }
+}
+
+impl NodeWalletApi<'_> {
+    /// Create a new wallet, optionally restoring it from `seed`
+    pub async fn wallet_create(&self, seed: Option<&Seed>) -> Result<WalletId, Error> {
+        let mut request = json!({
+            "action": "wallet_create",
+        });
+        if let Some(seed) = seed {
+            request["seed"] = json!(to_hex(&seed.0));
+        }
+        let response: WalletCreateResponse = self.api.rpc(request).await?;
+        Ok(response.wallet)
+    }
+
+    /// Add the private `key` to `wallet`, generating work for the account in the background when `work` is set
+    pub async fn wallet_add(&self, wallet: &WalletId, key: &PrivateKey, work: bool) -> Result<Address, Error> {
+        let request = json!({
+            "action": "wallet_add",
+            "wallet": wallet,
+            "key": to_hex(key.as_bytes()),
+            "work": work.to_string(),
+        });
+        let response: AccountResponse = self.api.rpc(request).await?;
+        Ok(response.account)
+    }
+
+    /// Create the next account of `wallet`, or the one at `index`
+    pub async fn account_create(&self, wallet: &WalletId, index: Option<u32>) -> Result<Address, Error> {
+        let mut request = json!({
+            "action": "account_create",
+            "wallet": wallet,
+        });
+        if let Some(index) = index {
+            request["index"] = json!(index.to_string());
+        }
+        let response: AccountResponse = self.api.rpc(request).await?;
+        Ok(response.account)
+    }
+
+    /// Create the next `count` accounts of `wallet`
+    pub async fn accounts_create(&self, wallet: &WalletId, count: u32) -> Result<Vec<Address>, Error> {
+        let request = json!({
+            "action": "accounts_create",
+            "wallet": wallet,
+            "count": count.to_string(),
+        });
+        let response: AccountsResponse = self.api.rpc(request).await?;
+        Ok(response.accounts)
+    }
+
+    /// Send `amount` from `source` to `destination`.
+    ///
+    /// When an `id` is given, the node does not send twice for the same `id` and returns the original
+    /// block instead, which makes retrying safe.
+    pub async fn send(&self, wallet: &WalletId, source: &Address, destination: &Address, amount: &Raw, id: Option<&str>) -> Result<BlockHash, Error> {
+        let mut request = json!({
+            "action": "send",
+            "wallet": wallet,
+            "source": source,
+            "destination": destination,
+            "amount": amount,
+        });
+        if let Some(id) = id {
+            request["id"] = json!(id);
+        }
+        let response: BlockResponse = self.api.rpc(request).await?;
+        Ok(response.block)
+    }
+
+    /// Receive the receivable `block` into `account`
+    pub async fn receive(&self, wallet: &WalletId, account: &Address, block: &BlockHash) -> Result<BlockHash, Error> {
+        let request = json!({
+            "action": "receive",
+            "wallet": wallet,
+            "account": account,
+            "block": block,
+        });
+        let response: BlockResponse = self.api.rpc(request).await?;
+        Ok(response.block)
+    }
+
+    /// Returns the balance of each account of `wallet`, ignoring accounts below `threshold`
+    pub async fn wallet_balances(&self, wallet: &WalletId, threshold: Option<&Raw>) -> Result<HashMap<Address, AccountBalance>, Error> {
+        let mut request = json!({
+            "action": "wallet_balances",
+            "wallet": wallet,
+        });
+        if let Some(threshold) = threshold {
+            request["threshold"] = json!(threshold);
+        }
+        let response: WalletBalancesResponse = self.api.rpc(request).await?;
+        Ok(response.balances)
+    }
+
+    /// Set the default `representative` of `wallet`, and of its existing accounts when
+    /// `update_existing_accounts` is set
+    pub async fn wallet_representative_set(&self, wallet: &WalletId, representative: &Address, update_existing_accounts: bool) -> Result<bool, Error> {
+        let request = json!({
+            "action": "wallet_representative_set",
+            "wallet": wallet,
+            "representative": representative,
+            "update_existing_accounts": update_existing_accounts.to_string(),
+        });
+        let response: WalletRepresentativeSetResponse = self.api.rpc(request).await?;
+        Ok(response.set)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::MockNode;
+    use std::str::FromStr;
+
+    #[test]
+    fn deserialize_wallet_responses() {
+        let response: WalletCreateResponse = serde_json::from_str(r#"{"wallet": "000D1BAEC8EC208142C99059B393051BAC8380F9B5A2E6B2489A277D81789F3F"}"#).unwrap();
+        assert_eq!(response.wallet, WalletId::from_str(MockNode::WALLET).unwrap());
+
+        let response: AccountsResponse = serde_json::from_str(r#"{"accounts": ""}"#).unwrap();
+        assert!(response.accounts.is_empty());
+
+        let json = r#"{"balances": {
+            "ban_1hgtqu7cmgxb66ta4gxt7coimqcxp86nzi5b7u14ip9zzpqr16a3dbqdja1f": {
+                "balance": "9900000000000000000000000000000",
+                "pending": "100"
+            }
+        }}"#;
+        let response: WalletBalancesResponse = serde_json::from_str(json).unwrap();
+        let balance = &response.balances[&Address("ban_1hgtqu7cmgxb66ta4gxt7coimqcxp86nzi5b7u14ip9zzpqr16a3dbqdja1f".into())];
+        assert_eq!(balance.pending, Raw::new(100u128));
+
+        let response: WalletRepresentativeSetResponse = serde_json::from_str(r#"{"set": "1"}"#).unwrap();
+        assert!(response.set);
+    }
+
+    #[tokio::test]
+    async fn serialize_wallet_requests() {
+        let node = MockNode::empty();
+        let api = node.api();
+        let wallets = api.node_wallet();
+        let wallet = wallets.wallet_create(Some(&Seed([1; 32]))).await.unwrap();
+        let source = Address(MockNode::ACCOUNT.into());
+        let destination = Address(MockNode::REPRESENTATIVE.into());
+        wallets.send(&wallet, &source, &destination, &Raw::new(5u128), Some("payout-1")).await.unwrap();
+        wallets.account_create(&wallet, Some(3)).await.unwrap();
+
+        assert_eq!(node.requests_for("wallet_create")[0]["seed"], "01".repeat(32));
+        assert_eq!(node.requests_for("send")[0], json!({
+            "action": "send",
+            "wallet": MockNode::WALLET,
+            "source": MockNode::ACCOUNT,
+            "destination": MockNode::REPRESENTATIVE,
+            "amount": "5",
+            "id": "payout-1",
+        }));
+        assert_eq!(node.requests_for("account_create")[0]["index"], "3");
+    }
+}
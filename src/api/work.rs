@@ -0,0 +1,254 @@
+use crate::{BananoApi, Error, encoding::deserialize_bool, types::{BlockHash, Difficulty, Work}};
+use async_trait::async_trait;
+use serde::{Deserialize, Deserializer};
+use serde_json::{json, Value};
+use serde_with::{serde_as, DisplayFromStr};
+
+/// Work generated by a node or a work server
+///
+/// [Nano documentation](https://docs.nano.org/commands/rpc-protocol/#work_generate)
+#[serde_as]
+#[derive(Debug, Deserialize)]
+pub struct WorkGenerate {
+    pub work: Work,
+    pub difficulty: Difficulty,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default)]
+    pub multiplier: Option<f64>,
+    /// Root the work was computed for, not returned by every work server
+    pub hash: Option<BlockHash>,
+}
+
+/// Work validation as seen by the node
+///
+/// [Nano documentation](https://docs.nano.org/commands/rpc-protocol/#work_validate)
+#[serde_as]
+#[derive(Debug, Deserialize)]
+pub struct WorkValidate {
+    /// Only available when a `difficulty` is set in the request
+    #[serde(default, deserialize_with = "deserialize_some_bool")]
+    pub valid: Option<bool>,
+    /// Work is valid for every block subtype
+    #[serde(deserialize_with = "deserialize_bool")]
+    pub valid_all: bool,
+    /// Work is valid for receive blocks
+    #[serde(deserialize_with = "deserialize_bool")]
+    pub valid_receive: bool,
+    pub difficulty: Difficulty,
+    #[serde_as(as = "DisplayFromStr")]
+    pub multiplier: f64,
+}
+
+fn deserialize_some_bool<'de, D>(deserializer: D) -> Result<Option<bool>, <D as Deserializer<'de>>::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserialize_bool(deserializer).map(Some)
+}
+
+impl BananoApi {
+    /// Ask the node to generate work for `root` (previous block hash, or public key for open blocks).
+    ///
+    /// Either a `difficulty` or a `multiplier` of the base difficulty can be requested, defaulting to
+    /// [Difficulty::DEFAULT]. The returned work is checked locally and rejected with [Error::InvalidWork]
+    /// when it does not reach the requested difficulty.
+    pub async fn work_generate(&self, root: &BlockHash, difficulty: Option<Difficulty>, multiplier: Option<f64>) -> Result<WorkGenerate, Error> {
+        let mut request = json!({
+            "action": "work_generate",
+            "hash": root,
+        });
+        let threshold = match (difficulty, multiplier) {
+            (Some(difficulty), _) => {
+                request["difficulty"] = json!(difficulty);
+                difficulty
+            }
+            (None, Some(multiplier)) => {
+                request["multiplier"] = json!(multiplier.to_string());
+                Difficulty::DEFAULT.from_multiplier(multiplier)
+            }
+            (None, None) => Difficulty::DEFAULT,
+        };
+        let work_generate: WorkGenerate = self.rpc(request).await?;
+        if !work_generate.work.is_valid(root, &threshold) {
+            return Err(Error::InvalidWork {
+                work: work_generate.work,
+                root: root.clone(),
+                difficulty: threshold,
+            });
+        }
+        Ok(work_generate)
+    }
+
+    /// Check whether `work` is valid for `root`, optionally against a specific `difficulty`
+    pub async fn work_validate(&self, work: &Work, root: &BlockHash, difficulty: Option<Difficulty>) -> Result<WorkValidate, Error> {
+        let mut request = json!({
+            "action": "work_validate",
+            "work": work,
+            "hash": root,
+        });
+        if let Some(difficulty) = difficulty {
+            request["difficulty"] = json!(difficulty);
+        }
+        self.rpc(request).await
+    }
+
+    /// Stop generating work for `root`
+    pub async fn work_cancel(&self, root: &BlockHash) -> Result<(), Error> {
+        let request = json!({
+            "action": "work_cancel",
+            "hash": root,
+        });
+        let _: Value = self.rpc(request).await?;
+        Ok(())
+    }
+}
+
+/// Client for dedicated work servers, which only understand the `work_*` actions
+///
+/// # Example:
+/// ```no_run
+/// use banano_rs::{api::WorkClient, types::{BlockHash, Difficulty}};
+/// use std::str::FromStr;
+///
+/// # async fn run() -> banano_rs::Result<()> {
+/// let work_server = WorkClient::new("http://localhost:7076".into());
+/// let root = BlockHash::from_str("718CC2121C3E641059BC1C2CFC45666C99E8AE922F7A807B7D07B62C995D79E2")?;
+/// let work = work_server.work_generate(&root, Some(Difficulty::DEFAULT), None).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct WorkClient {
+    api: BananoApi,
+}
+
+impl WorkClient {
+    /// Instanciate a work client using the work server URL
+    pub fn new(url: String) -> Self {
+        WorkClient {
+            api: BananoApi::new(url),
+        }
+    }
+
+    /// See [BananoApi::work_generate]
+    pub async fn work_generate(&self, root: &BlockHash, difficulty: Option<Difficulty>, multiplier: Option<f64>) -> Result<WorkGenerate, Error> {
+        self.api.work_generate(root, difficulty, multiplier).await
+    }
+
+    /// See [BananoApi::work_cancel]
+    pub async fn work_cancel(&self, root: &BlockHash) -> Result<(), Error> {
+        self.api.work_cancel(root).await
+    }
+}
+
+/// Anything able to compute proof of work for a block
+#[async_trait]
+pub trait WorkGenerator: Send + Sync {
+    /// Returns work for `root` reaching at least `threshold`
+    async fn generate_work(&self, root: &BlockHash, threshold: Difficulty) -> Result<Work, Error>;
+}
+
+#[async_trait]
+impl WorkGenerator for BananoApi {
+    async fn generate_work(&self, root: &BlockHash, threshold: Difficulty) -> Result<Work, Error> {
+        Ok(self.work_generate(root, Some(threshold), None).await?.work)
+    }
+}
+
+#[async_trait]
+impl WorkGenerator for WorkClient {
+    async fn generate_work(&self, root: &BlockHash, threshold: Difficulty) -> Result<Work, Error> {
+        Ok(self.work_generate(root, Some(threshold), None).await?.work)
+    }
+}
+
+/// Generate work on the CPU, on the blocking thread pool of the tokio runtime
+#[derive(Debug, Clone, Default)]
+pub struct LocalWorkGenerator;
+
+#[async_trait]
+impl WorkGenerator for LocalWorkGenerator {
+    async fn generate_work(&self, root: &BlockHash, threshold: Difficulty) -> Result<Work, Error> {
+        let root = root.clone();
+        tokio::task::spawn_blocking(move || Work::generate(&root, &threshold))
+            .await
+            .map_err(Error::WorkGenerationFailed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::MockNode;
+    use super::*;
+    use std::str::FromStr;
+
+    macro_rules! aw {
+        ($e:expr) => {
+            tokio_test::block_on($e)
+        };
+    }
+
+    fn root() -> BlockHash {
+        BlockHash::from_str("718CC2121C3E641059BC1C2CFC45666C99E8AE922F7A807B7D07B62C995D79E2").unwrap()
+    }
+
+    #[test]
+    fn rejects_work_below_threshold() {
+        let node = MockNode::empty();
+        node.on("work_generate", |request| Ok(json!({
+            "work": "2BF29EF00786A6BC",
+            "difficulty": "ffffffd21c3933f4",
+            "hash": request["hash"],
+        })));
+        let api = node.api();
+        assert_eq!(aw!(api.work_generate(&root(), None, None)).unwrap().work, Work::from_str("2BF29EF00786A6BC").unwrap());
+        match aw!(api.work_generate(&root(), None, Some(64.0))) {
+            Err(Error::InvalidWork { work, root: invalid_root, difficulty }) => {
+                assert_eq!(work, Work::from_str("2BF29EF00786A6BC").unwrap());
+                assert_eq!(invalid_root, root());
+                assert_eq!(difficulty, Difficulty::DEFAULT.from_multiplier(64.0));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(matches!(aw!(api.work_generate(&root(), Some(Difficulty(0xffffffe000000000)), None)), Err(Error::InvalidWork { .. })));
+
+        // work servers are not trusted to report the difficulty reached
+        node.on("work_generate", |request| Ok(json!({
+            "work": "0000000000000000",
+            "difficulty": "fffffe0000000000",
+            "hash": request["hash"],
+        })));
+        assert!(matches!(aw!(api.work_generate(&root(), None, None)), Err(Error::InvalidWork { .. })));
+    }
+
+    #[test]
+    fn validates_work() {
+        let api = MockNode::empty().api();
+        let work = Work::from_str("2BF29EF00786A6BC").unwrap();
+        let validation = aw!(api.work_validate(&work, &root(), None)).unwrap();
+        assert!(validation.valid_all && validation.valid_receive);
+        assert_eq!(validation.valid, None);
+        assert_eq!(validation.difficulty, Difficulty(0xffffffd21c3933f4));
+
+        let validation = aw!(api.work_validate(&work, &root(), Some(Difficulty(0xffffffe000000000)))).unwrap();
+        assert_eq!(validation.valid, Some(false));
+
+        let validation = aw!(api.work_validate(&Work::from_str("0000000000000000").unwrap(), &root(), None)).unwrap();
+        assert!(!validation.valid_all);
+    }
+
+    #[test]
+    fn cancels_work() {
+        let node = MockNode::empty();
+        assert!(aw!(node.api().work_cancel(&root())).is_ok());
+        node.on("work_cancel", |_| Ok(json!({"error": "Unable to cancel"})));
+        assert!(matches!(aw!(node.api().work_cancel(&root())), Err(Error::NodeError(_))));
+        assert_eq!(node.requests_for("work_cancel")[0]["hash"], json!(root()));
+    }
+
+    #[test]
+    fn generates_work_locally() {
+        let threshold = Difficulty(0xff00000000000000);
+        let work = aw!(LocalWorkGenerator.generate_work(&root(), threshold)).unwrap();
+        assert!(work.is_valid(&root(), &threshold));
+    }
+}
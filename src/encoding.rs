@@ -45,7 +45,7 @@ where
     <T as std::str::FromStr>::Err: std::fmt::Display,
 {
     let s: &str = Deserialize::deserialize(deserializer)?;
-    Ok(T::from_str(s).map_err(serde::de::Error::custom)?)
+    T::from_str(s).map_err(serde::de::Error::custom)
 }
 
 pub fn deserialize_from_string<'de, T, D>(
@@ -57,19 +57,61 @@ where
     <T as std::str::FromStr>::Err: std::fmt::Display,
 {
     let s: String = Deserialize::deserialize(deserializer)?;
-    Ok(T::from_str(s.as_str()).map_err(serde::de::Error::custom)?)
+    T::from_str(s.as_str()).map_err(serde::de::Error::custom)
+}
+
+/// Deserialize booleans sent by the node either as JSON booleans or as `"true"`, `"false"`, `"1"`, `"0"`
+pub fn deserialize_bool<'de, D>(deserializer: D) -> Result<bool, <D as Deserializer<'de>>::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Bool {
+        Bool(bool),
+        String(String),
+    }
+
+    match Bool::deserialize(deserializer)? {
+        Bool::Bool(b) => Ok(b),
+        Bool::String(s) => match s.as_str() {
+            "true" | "1" => Ok(true),
+            "false" | "0" => Ok(false),
+            _ => Err(serde::de::Error::custom(format!("invalid boolean: {}", s))),
+        },
+    }
+}
+
+/// Deserialize collections which the node sends as an empty string when they have no element
+pub fn deserialize_empty_as_default<'de, T, D>(deserializer: D) -> Result<T, <D as Deserializer<'de>>::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + Default,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum MaybeEmpty<T> {
+        Value(T),
+        String(String),
+    }
+
+    match MaybeEmpty::deserialize(deserializer)? {
+        MaybeEmpty::Value(value) => Ok(value),
+        MaybeEmpty::String(s) if s.is_empty() => Ok(T::default()),
+        MaybeEmpty::String(s) => Err(serde::de::Error::custom(format!("unexpected string: {}", s))),
+    }
 }
 
 pub fn blake2b(size: usize, data: &[u8]) -> Box<[u8]> {
     let mut blake = VarBlake2b::new(size).expect("Output size was zero");
-    blake.update(&data);
+    blake.update(data);
     blake.finalize_boxed()
 }
 
 /// Use this instead of [blake2b] to probably prevent an allocation.
 pub fn blake2b_callback(size: usize, data: &[u8], f: impl FnOnce(&[u8])) {
     let mut blake = VarBlake2b::new(size).expect("Output size was zero");
-    blake.update(&data);
+    blake.update(data);
     blake.finalize_variable(f)
 }
 
@@ -113,7 +155,7 @@ pub fn decode_banano_base_32(s: &str) -> Result<BitVec<Msb0, u8>, Error> {
 /// * serde implementations to (de)serialize hex strings.
 /// * `pub fn as_bytes(&self) -> &[u8]`
 /// * `pub fn as_hex(&self) -> String`
-/// * `TryFrom<&[u8]>` implementation.
+/// * `TryFrom<&[u8]>` and `From<[u8; LEN]>` implementations.
 /// * [FromStr] implementation, which parses hex into its type.
 /// * [Debug] implementation, which displays as StructName(H3XSTR1NG), e.g. Work(A1B2C3).
 /// * [std::fmt::Display] implementation, which displays the hex string.
@@ -159,22 +201,22 @@ use std::str::FromStr; // don't forget `FromStr`!
             }
 
             pub fn as_hex(&self) -> String {
-                crate::encoding::to_hex(&self.0)
+                $crate::encoding::to_hex(&self.0)
             }
 
             pub fn as_hex_lower(&self) -> String {
-                crate::encoding::to_hex_lower(&self.0)
+                $crate::encoding::to_hex_lower(&self.0)
             }
         }
 
         impl ::std::str::FromStr for $struct {
-            type Err = crate::Error;
+            type Err = $crate::Error;
 
-            fn from_str(s: &str) -> crate::Result<Self> {
+            fn from_str(s: &str) -> $crate::Result<Self> {
                 use ::std::convert::TryFrom;
 
-                crate::encoding::expect_len(s.len(), Self::LEN * 2, $description)?;
-                let vec = hex::decode(s.as_bytes()).map_err(|e| crate::Error::FromHexError {
+                $crate::encoding::expect_len(s.len(), Self::LEN * 2, $description)?;
+                let vec = hex::decode(s.as_bytes()).map_err(|e| $crate::Error::FromHexError {
                     msg: String::from($description),
                     source: e,
                 })?;
@@ -196,19 +238,25 @@ use std::str::FromStr; // don't forget `FromStr`!
                     f,
                     "{}({})",
                     stringify!($struct),
-                    crate::encoding::to_hex(self.0.as_ref()),
+                    $crate::encoding::to_hex(self.0.as_ref()),
                 )
             }
         }
 
         impl ::std::convert::TryFrom<&[u8]> for $struct {
-            type Error = crate::Error;
+            type Error = $crate::Error;
 
-            fn try_from(v: &[u8]) -> crate::Result<Self> {
+            fn try_from(v: &[u8]) -> $crate::Result<Self> {
                 Ok(Self(<[u8; Self::LEN]>::try_from(v)?))
             }
         }
 
+        impl ::std::convert::From<[u8; $length]> for $struct {
+            fn from(v: [u8; $length]) -> Self {
+                Self(v)
+            }
+        }
+
         impl ::std::fmt::UpperHex for $struct {
             fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
                 write!(f, "{}", self.as_hex())
@@ -241,17 +289,36 @@ use std::str::FromStr; // don't forget `FromStr`!
                 D: serde::Deserializer<'de>,
             {
                 let s: String = serde::Deserialize::deserialize(deserializer)?;
-                Ok(Self::from_str(&s).map_err(serde::de::Error::custom)?)
+                Self::from_str(&s).map_err(serde::de::Error::custom)
             }
         }
     };
 }
 
+pub fn expect_len(got_len: usize, expected_len: usize, msg: &str) -> crate::Result<()> {
+    if got_len != expected_len {
+        return Err(crate::errors::Error::WrongLength {
+            msg: msg.to_string(),
+            expected: expected_len,
+            found: got_len,
+        });
+    }
+    Ok(())
+}
+
+pub fn len_err_msg(got_len: usize, expected_len: usize, msg: &str) -> String {
+    format!(
+        "{} is the wrong length: got: {} expected: {}",
+        msg, got_len, expected_len,
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
+    #[allow(clippy::useless_transmute)]
     fn encode_decode() {
         let bits: BitVec<Msb0, u8> =
             bitvec![Msb0, u8; 1, 1, 1, 1, 1, 0, 0, 0, 0, 0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 0];
@@ -326,21 +393,3 @@ mod tests {
     }
     */
 }
-
-pub fn expect_len(got_len: usize, expected_len: usize, msg: &str) -> crate::Result<()> {
-    if got_len != expected_len {
-        return Err(crate::errors::Error::WrongLength {
-            msg: msg.to_string(),
-            expected: expected_len,
-            found: got_len,
-        });
-    }
-    Ok(())
-}
-
-pub fn len_err_msg(got_len: usize, expected_len: usize, msg: &str) -> String {
-    format!(
-        "{} is the wrong length: got: {} expected: {}",
-        msg, got_len, expected_len,
-    )
-}
@@ -37,4 +37,108 @@ pub enum Error {
     },
 	#[error("Try from slice error")]
     TryFromSliceError(#[from] std::array::TryFromSliceError),
+    #[error("JSON error")]
+    JsonError(#[from] serde_json::Error),
+    #[error("Node error: {0}")]
+    NodeError(String),
+    #[error("Work {work} does not reach difficulty {difficulty} for {root}")]
+    InvalidWork {
+        work: crate::types::Work,
+        root: crate::types::BlockHash,
+        difficulty: crate::types::Difficulty,
+    },
+    #[error("Work generation failed")]
+    WorkGenerationFailed(#[source] tokio::task::JoinError),
+    #[error("Timed out")]
+    Timeout,
+    #[error("No RPC endpoint available")]
+    NoEndpointAvailable,
+    #[error("Invalid HTTP header {0}")]
+    InvalidHeader(String),
+    #[error("Rate limited by the RPC endpoint")]
+    RateLimited {
+        /// How long the endpoint asked to wait, from its `Retry-After` header
+        retry_after: Option<std::time::Duration>,
+    },
+    #[cfg(feature = "websocket")]
+    #[error("Websocket error")]
+    WebSocketError(#[source] Box<tokio_tungstenite::tungstenite::Error>),
+    #[error("Websocket connection closed")]
+    WebSocketClosed,
+    #[error("Block rejected: {0}")]
+    ProcessError(#[from] ProcessError),
+    #[error("Insufficient balance: {amount} requested, {balance} available")]
+    InsufficientBalance {
+        balance: crate::Raw,
+        amount: crate::Raw,
+    },
+    #[error("Account {} is not part of the wallet", .address.0)]
+    AccountNotInWallet {
+        address: crate::Address,
+    },
+    #[error("Account {} is not opened yet", .address.0)]
+    AccountNotOpened {
+        address: crate::Address,
+    },
+    #[error("Block {hash} is not a send to {}", .address.0)]
+    NotASend {
+        hash: crate::types::BlockHash,
+        address: crate::Address,
+    },
+    #[error("Receiving {amount} would overflow the balance of {}", .address.0)]
+    BalanceOverflow {
+        address: crate::Address,
+        amount: crate::Raw,
+    },
+    #[error("Wallet has no seed to derive accounts from")]
+    NoSeed,
+}
+
+#[cfg(feature = "websocket")]
+impl From<tokio_tungstenite::tungstenite::Error> for Error {
+    fn from(error: tokio_tungstenite::tungstenite::Error) -> Self {
+        Error::WebSocketError(Box::new(error))
+    }
+}
+
+/// Reasons for a node to reject a block sent with `process`
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ProcessError {
+    #[error("Fork")]
+    Fork,
+    #[error("Old block")]
+    Old,
+    #[error("Bad signature")]
+    BadSignature,
+    #[error("Insufficient work")]
+    InsufficientWork,
+    #[error("Gap previous block")]
+    GapPrevious,
+    #[error("Gap source block")]
+    GapSource,
+    #[error("Balance and amount delta do not match")]
+    BalanceMismatch,
+    #[error("Negative spend")]
+    NegativeSpend,
+    #[error("Unreceivable")]
+    Unreceivable,
+    #[error("{0}")]
+    Other(String),
+}
+
+impl From<&str> for ProcessError {
+    fn from(error: &str) -> Self {
+        match error {
+            "Fork" => ProcessError::Fork,
+            "Old block" => ProcessError::Old,
+            "Bad signature" => ProcessError::BadSignature,
+            "Block work is less than threshold" | "Block work is insufficient" => ProcessError::InsufficientWork,
+            "Gap previous block" => ProcessError::GapPrevious,
+            "Gap source block" => ProcessError::GapSource,
+            "Balance and amount delta do not match" => ProcessError::BalanceMismatch,
+            "Negative spend" => ProcessError::NegativeSpend,
+            "Unreceivable" => ProcessError::Unreceivable,
+            other => ProcessError::Other(other.into()),
+        }
+    }
 }
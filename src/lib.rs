@@ -5,8 +5,12 @@ pub mod types;
 pub mod units;
 pub mod encoding;
 pub mod api;
+pub mod payments;
+pub mod wallet;
+#[cfg(feature = "websocket")]
+pub mod websocket;
 
 pub use api::BananoApi;
-pub use errors::{Error, Result};
+pub use errors::{Error, ProcessError, Result};
 pub use units::raw::Raw;
 pub use types::Address;
@@ -0,0 +1,574 @@
+//! Incoming payments of watched accounts
+//!
+//! # Example:
+//! ```no_run
+//! use banano_rs::{Address, BananoApi, payments::PaymentWatcher};
+//! use futures::StreamExt;
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), banano_rs::Error> {
+//!     let banano = BananoApi::new("https://kaliumapi.appditto.com/api".into());
+//!     let address = Address("ban_1hgtqu7cmgxb66ta4gxt7coimqcxp86nzi5b7u14ip9zzpqr16a3dbqdja1f".into());
+//!     let mut payments = Box::pin(PaymentWatcher::new(banano, vec![address]).payments());
+//!     while let Some(payment) = payments.next().await {
+//!         let payment = payment?;
+//!         println!("{} sent {} to {}", payment.sender.0, payment.amount, payment.account.0);
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use crate::{Address, BananoApi, Error, Raw, api::ReceivableOptions, types::BlockHash};
+#[cfg(feature = "websocket")]
+use crate::{types::BlockSubtype, websocket::{Confirmation, ConfirmationOptions, Subscription, WebSocketSession}};
+use futures::{stream, Stream};
+#[cfg(feature = "websocket")]
+use futures::{future::{self, Either}, StreamExt};
+use log::{debug, warn};
+#[cfg(feature = "websocket")]
+use std::pin::Pin;
+use std::{collections::{HashMap, HashSet, VecDeque}, sync::{Arc, Mutex}, time::Duration};
+use tokio::time::{sleep_until, Instant};
+
+/// Payments remembered once received, to skip the confirmation of their receive block
+const POCKETED_CAPACITY: usize = 1024;
+
+#[cfg(feature = "websocket")]
+type Confirmations = Pin<Box<dyn Stream<Item = Result<Confirmation, Error>> + Send>>;
+
+/// Funds sent to a watched account
+#[derive(Debug, Clone, PartialEq)]
+pub struct Payment {
+    /// Watched account the funds were sent to
+    pub account: Address,
+    pub sender: Address,
+    pub amount: Raw,
+    /// Hash of the send block, which the account receives to pocket the funds
+    pub hash: BlockHash,
+    pub confirmed: bool,
+}
+
+/// Options of a [PaymentWatcher]
+#[derive(Debug, Clone)]
+pub struct WatcherOptions {
+    /// Delay between two polls of `receivable`
+    pub poll_interval: Duration,
+    /// Delay between two polls of `receivable` while websocket confirmations are received, to catch
+    /// payments sent while it was disconnected
+    pub websocket_poll_interval: Duration,
+    /// Ignore payments below this amount
+    pub threshold: Option<Raw>,
+    /// Also yield payments before their confirmation, which are then yielded again once confirmed
+    pub include_unconfirmed: bool,
+}
+
+impl Default for WatcherOptions {
+    fn default() -> Self {
+        WatcherOptions {
+            poll_interval: Duration::from_secs(5),
+            websocket_poll_interval: Duration::from_secs(60),
+            threshold: None,
+            include_unconfirmed: false,
+        }
+    }
+}
+
+/// Watches accounts for incoming payments
+///
+/// Payments are found by polling `receivable`, and as soon as their send is confirmed with a
+/// [WebSocketSession](crate::websocket::WebSocketSession) when the `websocket` feature is enabled.
+/// Each payment is yielded once, unless [include_unconfirmed](WatcherOptions::include_unconfirmed)
+/// is set: it is then yielded again when confirmed. With the websocket, payments received by the
+/// watched accounts before being found are yielded from the confirmation of their receive block.
+pub struct PaymentWatcher {
+    api: BananoApi,
+    accounts: Vec<Address>,
+    options: WatcherOptions,
+    forgotten: Arc<Mutex<Vec<BlockHash>>>,
+    #[cfg(feature = "websocket")]
+    session: Option<WebSocketSession>,
+}
+
+impl PaymentWatcher {
+    /// Watch `accounts` by polling the node of `api`
+    pub fn new(api: BananoApi, accounts: Vec<Address>) -> Self {
+        PaymentWatcher {
+            api,
+            accounts,
+            options: WatcherOptions::default(),
+            forgotten: Arc::new(Mutex::new(Vec::new())),
+            #[cfg(feature = "websocket")]
+            session: None,
+        }
+    }
+
+    pub fn with_options(mut self, options: WatcherOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Get payments from the confirmations of `session`, which gets subscribed to the watched
+    /// accounts. Polling goes on at [websocket_poll_interval](WatcherOptions::websocket_poll_interval),
+    /// or at [poll_interval](WatcherOptions::poll_interval) if the session gives up.
+    #[cfg(feature = "websocket")]
+    pub fn with_websocket(mut self, session: WebSocketSession) -> Self {
+        self.session = Some(session);
+        self
+    }
+
+    /// Payments already yielded which are yielded again if still receivable at the next poll
+    pub(crate) fn forgotten(&self) -> Arc<Mutex<Vec<BlockHash>>> {
+        self.forgotten.clone()
+    }
+
+    /// Payments to the watched accounts, including those already receivable. Failed polls are
+    /// yielded as errors and tried again at the next interval.
+    pub fn payments(self) -> impl Stream<Item = Result<Payment, Error>> {
+        stream::unfold(self.watcher(), |mut watcher| async move {
+            watcher.next().await.map(|payment| (payment, watcher))
+        })
+    }
+
+    /// State of the [payments](PaymentWatcher::payments) stream, subscribed to the confirmations
+    /// of the watched accounts
+    fn watcher(self) -> Watcher {
+        #[cfg(feature = "websocket")]
+        let accounts = self.accounts.clone();
+        #[cfg(feature = "websocket")]
+        let confirmations = self.session.and_then(|session| {
+            let options = ConfirmationOptions::accounts(accounts);
+            match session.subscribe(Subscription::Confirmation(options)) {
+                Ok(()) => Some(Box::pin(session.confirmations()) as Confirmations),
+                Err(error) => {
+                    warn!("Cannot subscribe to confirmations, polling only: {}", error);
+                    None
+                }
+            }
+        });
+        Watcher {
+            api: self.api,
+            accounts: self.accounts,
+            options: self.options,
+            forgotten: self.forgotten,
+            #[cfg(feature = "websocket")]
+            confirmations,
+            seen: HashMap::new(),
+            pocketed: HashSet::new(),
+            pocketed_order: VecDeque::new(),
+            unresolved: Vec::new(),
+            backlog: VecDeque::new(),
+            next_poll: Instant::now(),
+        }
+    }
+}
+
+struct Watcher {
+    api: BananoApi,
+    accounts: Vec<Address>,
+    options: WatcherOptions,
+    forgotten: Arc<Mutex<Vec<BlockHash>>>,
+    #[cfg(feature = "websocket")]
+    confirmations: Option<Confirmations>,
+    /// Account and confirmation of the payments already yielded
+    seen: HashMap<BlockHash, (Address, bool)>,
+    /// Payments already yielded and received since, most recent last
+    pocketed: HashSet<BlockHash>,
+    pocketed_order: VecDeque<BlockHash>,
+    /// Account, amount and hash of the payments received before being found whose sender could
+    /// not be fetched yet
+    unresolved: Vec<(Address, Raw, BlockHash)>,
+    backlog: VecDeque<Payment>,
+    next_poll: Instant,
+}
+
+impl Watcher {
+    async fn next(&mut self) -> Option<Result<Payment, Error>> {
+        loop {
+            if let Some(payment) = self.backlog.pop_front() {
+                return Some(Ok(payment));
+            }
+            if let Err(error) = self.wait().await {
+                return Some(Err(error));
+            }
+        }
+    }
+
+    fn poll_interval(&self) -> Duration {
+        #[cfg(feature = "websocket")]
+        if self.confirmations.is_some() {
+            return self.options.websocket_poll_interval;
+        }
+        self.options.poll_interval
+    }
+
+    /// Wait for a confirmation or the next poll
+    #[cfg(feature = "websocket")]
+    async fn wait(&mut self) -> Result<(), Error> {
+        let confirmations = match self.confirmations.as_mut() {
+            Some(confirmations) => confirmations,
+            None => {
+                sleep_until(self.next_poll).await;
+                return self.poll().await;
+            }
+        };
+        // the select is done in its own statement to release the borrow of `confirmations`
+        let confirmation = match future::select(confirmations.next(), Box::pin(sleep_until(self.next_poll))).await {
+            Either::Left((confirmation, _)) => Some(confirmation),
+            Either::Right(_) => None,
+        };
+        match confirmation {
+            Some(Some(Ok(confirmation))) => self.confirmed(confirmation).await?,
+            Some(Some(Err(error))) => self.polling_only(&error.to_string()),
+            Some(None) => self.polling_only("session ended"),
+            None => return self.poll().await,
+        }
+        Ok(())
+    }
+
+    /// Stop waiting for confirmations and poll more often
+    #[cfg(feature = "websocket")]
+    fn polling_only(&mut self, reason: &str) {
+        warn!("No more websocket confirmations, polling only: {}", reason);
+        self.confirmations = None;
+        self.next_poll = Instant::now();
+    }
+
+    #[cfg(not(feature = "websocket"))]
+    async fn wait(&mut self) -> Result<(), Error> {
+        sleep_until(self.next_poll).await;
+        self.poll().await
+    }
+
+    /// Record a payment, queueing it unless it was already yielded
+    fn found(&mut self, payment: Payment) {
+        let new = match self.seen.get(&payment.hash) {
+            Some((_, true)) => false,
+            Some((_, false)) => payment.confirmed,
+            None => payment.confirmed || self.options.include_unconfirmed,
+        };
+        if new {
+            self.seen.insert(payment.hash.clone(), (payment.account.clone(), payment.confirmed));
+            self.backlog.push_back(payment);
+        }
+    }
+
+    /// Remember that the payment `hash`, already yielded, was received
+    fn pocketed(&mut self, hash: BlockHash) {
+        if !self.pocketed.insert(hash.clone()) {
+            return;
+        }
+        self.pocketed_order.push_back(hash);
+        if self.pocketed_order.len() > POCKETED_CAPACITY {
+            if let Some(oldest) = self.pocketed_order.pop_front() {
+                self.pocketed.remove(&oldest);
+            }
+        }
+    }
+
+    /// Record the payment of a confirmed send to a watched account, or of the send received by a
+    /// confirmed receive block of a watched account
+    #[cfg(feature = "websocket")]
+    async fn confirmed(&mut self, confirmation: Confirmation) -> Result<(), Error> {
+        let below_threshold = matches!(&self.options.threshold, Some(threshold) if confirmation.amount < *threshold);
+        if below_threshold {
+            return Ok(());
+        }
+        match confirmation.subtype() {
+            Some(BlockSubtype::Send) => {
+                let account = match confirmation.destination() {
+                    Some(account) if self.accounts.contains(&account) => account,
+                    _ => return Ok(()),
+                };
+                self.found(Payment {
+                    account,
+                    sender: confirmation.account,
+                    amount: confirmation.amount,
+                    hash: confirmation.hash,
+                    confirmed: true,
+                });
+            }
+            Some(BlockSubtype::Receive) | Some(BlockSubtype::Open) if self.accounts.contains(&confirmation.account) => {
+                let hash = match &confirmation.contents {
+                    Some(contents) => contents.block.link.to_block_hash(),
+                    None => return Ok(()),
+                };
+                let known = matches!(self.seen.get(&hash), Some((_, true)))
+                    || self.pocketed.contains(&hash)
+                    || self.unresolved.iter().any(|(_, _, unresolved)| *unresolved == hash);
+                if known {
+                    return Ok(());
+                }
+                debug!("Payment {} was received before being found", hash);
+                return self.received(confirmation.account, confirmation.amount, hash).await;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Record the payment `hash` of `amount` already received by `account`, once its sender is
+    /// fetched from the send block. It is tried again at the next poll if that fails.
+    async fn received(&mut self, account: Address, amount: Raw, hash: BlockHash) -> Result<(), Error> {
+        let sender = match self.api.block_info(&hash).await {
+            Ok(info) => info.block_account,
+            Err(error) => {
+                self.unresolved.push((account, amount, hash));
+                return Err(error);
+            }
+        };
+        self.found(Payment {
+            account,
+            sender,
+            amount,
+            hash: hash.clone(),
+            confirmed: true,
+        });
+        self.seen.remove(&hash);
+        self.pocketed(hash);
+        Ok(())
+    }
+
+    /// Find the receivable blocks of every watched account, and the senders of the payments not
+    /// resolved yet. Failures do not prevent the other accounts from being polled, the first one is
+    /// returned.
+    async fn poll(&mut self) -> Result<(), Error> {
+        self.next_poll = Instant::now() + self.poll_interval();
+        for hash in self.forgotten.lock().unwrap().drain(..) {
+            self.seen.remove(&hash);
+        }
+        let mut failure = None;
+        for (account, amount, hash) in std::mem::take(&mut self.unresolved) {
+            if let Err(error) = self.received(account, amount, hash).await {
+                failure.get_or_insert(error);
+            }
+        }
+        for account in self.accounts.clone() {
+            if let Err(error) = self.poll_account(&account).await {
+                warn!("Cannot poll {}: {}", account.0, error);
+                failure.get_or_insert(error);
+            }
+        }
+        debug!("Polled {} watched accounts", self.accounts.len());
+        failure.map_or(Ok(()), Err)
+    }
+
+    /// Find the receivable blocks of `account`
+    async fn poll_account(&mut self, account: &Address) -> Result<(), Error> {
+        let options = ReceivableOptions {
+            threshold: self.options.threshold.clone(),
+            source: true,
+            include_only_confirmed: Some(!self.options.include_unconfirmed),
+            ..Default::default()
+        };
+        let blocks = self.api.receivable(account, options).await?;
+
+        // blocks received since the last poll will not show up again
+        let received: Vec<BlockHash> = self.seen
+            .iter()
+            .filter(|(hash, (seen_account, _))| seen_account == account && !blocks.contains_key(*hash))
+            .map(|(hash, _)| hash.clone())
+            .collect();
+        for hash in received {
+            self.seen.remove(&hash);
+            self.pocketed(hash);
+        }
+
+        let confirmed = if self.options.include_unconfirmed {
+            let unknown: Vec<BlockHash> = blocks
+                .keys()
+                .filter(|hash| !matches!(self.seen.get(*hash), Some((_, true))))
+                .cloned()
+                .collect();
+            if unknown.is_empty() {
+                HashMap::new()
+            } else {
+                self.api.blocks_info(&unknown).await?.into_iter().map(|(hash, info)| (hash, info.confirmed)).collect()
+            }
+        } else {
+            HashMap::new()
+        };
+
+        for (hash, block) in blocks {
+            let sender = match block.source {
+                Some(sender) => sender,
+                None => continue,
+            };
+            let confirmed = !self.options.include_unconfirmed || confirmed.get(&hash).copied().unwrap_or(true);
+            self.found(Payment {
+                account: account.clone(),
+                sender,
+                amount: block.amount,
+                hash,
+                confirmed,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::MockNode;
+    use futures::StreamExt;
+
+    fn options() -> WatcherOptions {
+        WatcherOptions {
+            poll_interval: Duration::from_millis(10),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn polls_receivable_blocks_once() {
+        let node = MockNode::new();
+        let account = Address("ban_1fomoz167m7o38gw4rzt7hz67oq6itejpt4yocrfywujbpatd711cjew8gjj".into());
+        let sender = Address(MockNode::ACCOUNT.into());
+        let first = node.add_receivable(&account, &sender, Raw::new(1u32)).unwrap();
+
+        let watcher = PaymentWatcher::new(node.api(), vec![account.clone()]).with_options(options());
+        let mut payments = Box::pin(watcher.payments());
+        let payment = payments.next().await.unwrap().unwrap();
+        assert_eq!(payment, Payment {
+            account: account.clone(),
+            sender: sender.clone(),
+            amount: Raw::new(1u32),
+            hash: first,
+            confirmed: true,
+        });
+
+        let second = node.add_receivable(&account, &sender, Raw::new(2u32)).unwrap();
+        let payment = tokio::time::timeout(Duration::from_secs(1), payments.next()).await.unwrap().unwrap().unwrap();
+        assert_eq!(payment.hash, second);
+        assert!(tokio::time::timeout(Duration::from_millis(50), payments.next()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn yields_unconfirmed_payments_again_once_confirmed() {
+        let node = MockNode::new().with_auto_confirm(false);
+        let account = Address("ban_1fomoz167m7o38gw4rzt7hz67oq6itejpt4yocrfywujbpatd711cjew8gjj".into());
+        let hash = node.add_receivable(&account, &Address(MockNode::ACCOUNT.into()), Raw::new(1u32)).unwrap();
+
+        let options = WatcherOptions {
+            include_unconfirmed: true,
+            ..options()
+        };
+        let mut payments = Box::pin(PaymentWatcher::new(node.api(), vec![account]).with_options(options).payments());
+        assert!(!payments.next().await.unwrap().unwrap().confirmed);
+        node.set_confirmed(&hash, true);
+        let payment = payments.next().await.unwrap().unwrap();
+        assert!(payment.confirmed);
+        assert_eq!(payment.hash, hash);
+    }
+
+    #[cfg(feature = "websocket")]
+    #[tokio::test]
+    async fn skips_polled_payments_already_confirmed() {
+        let node = MockNode::new();
+        let account = Address("ban_1fomoz167m7o38gw4rzt7hz67oq6itejpt4yocrfywujbpatd711cjew8gjj".into());
+        let sender = Address(MockNode::ACCOUNT.into());
+        let hash = node.add_receivable(&account, &sender, Raw::new(1u32)).unwrap();
+        let confirmation: Confirmation = serde_json::from_value(serde_json::json!({
+            "account": sender,
+            "amount": "1",
+            "hash": hash,
+            "confirmation_type": "active_quorum",
+            "block": node.block(&hash).map(|block| {
+                let mut block = serde_json::json!(block);
+                block["subtype"] = "send".into();
+                block
+            }),
+        })).unwrap();
+
+        let mut watcher = PaymentWatcher::new(node.api(), vec![account.clone()]).with_options(options()).watcher();
+        watcher.confirmed(confirmation).await.unwrap();
+        assert_eq!(watcher.backlog.pop_front().unwrap().account, account);
+        watcher.poll().await.unwrap();
+        assert!(watcher.backlog.is_empty());
+    }
+
+    /// Node where `account` received a payment of 5 from [MockNode::ACCOUNT] in the block confirmed
+    /// by the returned confirmation, along with the hash of the send block
+    #[cfg(feature = "websocket")]
+    async fn received_payment(account_seed: u8) -> (MockNode, Address, BlockHash, serde_json::Value) {
+        use crate::types::{PrivateKey, PublicKey, Seed, StateBlock};
+
+        let node = MockNode::empty();
+        let key = PrivateKey::from_seed(Seed([account_seed; 32]), 0);
+        let account = Address::from(PublicKey::from(&key));
+        let sender = Address(MockNode::ACCOUNT.into());
+        let open = node.add_account(&account, Raw::new(10u32), &account).unwrap();
+        let send = node.add_receivable(&account, &sender, Raw::new(5u32)).unwrap();
+        let mut receive = StateBlock {
+            account: account.clone(),
+            previous: open,
+            representative: account.clone(),
+            balance: Raw::new(15u32),
+            link: send.clone().into(),
+            signature: None,
+            work: None,
+        };
+        receive.sign(&key).unwrap();
+        let hash = node.api().process(&receive, Default::default()).await.unwrap();
+        let mut block = serde_json::json!(receive);
+        block["subtype"] = "receive".into();
+        let confirmation = serde_json::json!({
+            "account": account,
+            "amount": "5",
+            "hash": hash,
+            "confirmation_type": "active_quorum",
+            "block": block,
+        });
+        (node, account, send, confirmation)
+    }
+
+    #[cfg(feature = "websocket")]
+    #[tokio::test]
+    async fn yields_payments_received_before_being_found() {
+        let (node, account, send, confirmation) = received_payment(8).await;
+        let mut watcher = PaymentWatcher::new(node.api(), vec![account.clone()]).with_options(options()).watcher();
+        watcher.confirmed(serde_json::from_value(confirmation.clone()).unwrap()).await.unwrap();
+        assert_eq!(watcher.backlog.pop_front().unwrap(), Payment {
+            account,
+            sender: Address(MockNode::ACCOUNT.into()),
+            amount: Raw::new(5u32),
+            hash: send,
+            confirmed: true,
+        });
+        // the same receive block, backfilled after a reconnection
+        watcher.confirmed(serde_json::from_value(confirmation).unwrap()).await.unwrap();
+        watcher.poll().await.unwrap();
+        assert!(watcher.backlog.is_empty());
+    }
+
+    #[cfg(feature = "websocket")]
+    #[tokio::test]
+    async fn resolves_received_payments_again_after_a_failure() {
+        let (node, account, send, confirmation) = received_payment(9).await;
+        let unreachable = MockNode::empty();
+        unreachable.on("block_info", |_| Err(Error::Timeout));
+        let mut watcher = PaymentWatcher::new(unreachable.api(), vec![account.clone()]).with_options(options()).watcher();
+        assert!(matches!(watcher.confirmed(serde_json::from_value(confirmation.clone()).unwrap()).await, Err(Error::Timeout)));
+        // the same receive block, backfilled after a reconnection
+        watcher.confirmed(serde_json::from_value(confirmation).unwrap()).await.unwrap();
+        assert!(watcher.backlog.is_empty());
+
+        watcher.api = node.api();
+        watcher.poll().await.unwrap();
+        assert_eq!(watcher.backlog.pop_front().unwrap().hash, send);
+        watcher.poll().await.unwrap();
+        assert!(watcher.backlog.is_empty());
+    }
+
+    #[tokio::test]
+    async fn polls_every_account_despite_failures() {
+        let node = MockNode::new();
+        let account = Address("ban_1fomoz167m7o38gw4rzt7hz67oq6itejpt4yocrfywujbpatd711cjew8gjj".into());
+        let invalid = Address("ban_1invalid".into());
+        let hash = node.add_receivable(&account, &Address(MockNode::ACCOUNT.into()), Raw::new(1u32)).unwrap();
+
+        let mut watcher = PaymentWatcher::new(node.api(), vec![invalid, account]).with_options(options()).watcher();
+        assert!(matches!(watcher.poll().await, Err(Error::NodeError(_))));
+        assert_eq!(watcher.backlog.pop_front().unwrap().hash, hash);
+    }
+}
@@ -4,7 +4,7 @@ use std::{convert::TryInto, ops::Deref};
 use serde::{Serialize, Deserialize};
 
 /// Address
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Address(pub String);
 
 impl Address {
@@ -1,10 +1,38 @@
-use crate::hexify;
+use crate::{hexify, Address, Raw, encoding::blake2b};
+use super::{PrivateKey, PublicKey, Work};
 use serde::{Serialize, Deserialize};
 use strum_macros::EnumString;
 use anyhow::anyhow;
 use std::{convert::TryFrom, str::FromStr};
 
 hexify!(BlockHash, 32, "Block hash", "40DB7EC1F71F7B3B66982007F20E687148BDB875E533121259C0BF69AEFE88D3");
+hexify!(Link, 32, "Block link", "0000000000000000000000000000000000000000000000000000000000000000");
+hexify!(QualifiedRoot, 64, "Qualified root (root followed by previous block hash)", "AEB05C09C41C5A9F8B4FC59C8B0DC2E8A0F3A2F2F00D5AA8D02A0AD3C7D1F0E12F20C3F42DD5F0A7B3C6D8E6E90C1D8E1F8D2D31A0D6A86E8C6B4F5D0B2E3F1A");
+hexify!(Signature, 64, "Block signature", "5B11B17DB9C8FE0CC58CAC6A6EECEF9CB122DA8A81C6D3DB1B5EE3AB065AA8F8CB1D6765C8EB91B58530C5FF5987AD95E6D34BB57F44257E20795EE412E61600");
+
+impl Link {
+    /// Interpret the link as the destination account of a send block
+    pub fn to_address(&self) -> Address {
+        PublicKey(self.0).into()
+    }
+
+    /// Interpret the link as the send block pocketed by a receive block
+    pub fn to_block_hash(&self) -> BlockHash {
+        BlockHash(self.0)
+    }
+}
+
+impl From<BlockHash> for Link {
+    fn from(hash: BlockHash) -> Self {
+        Link(hash.0)
+    }
+}
+
+impl From<PublicKey> for Link {
+    fn from(key: PublicKey) -> Self {
+        Link(key.0)
+    }
+}
 
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize, EnumString)]
 #[serde(rename_all = "snake_case")]
@@ -51,6 +79,77 @@ impl TryFrom<u8> for BlockType {
     }
 }
 
+/// Subtype of a state block, as understood by the `process` RPC
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize, EnumString)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum BlockSubtype {
+    Send,
+    Receive,
+    Open,
+    Change,
+    Epoch,
+}
+
+/// State block, the only block type used to move funds on the Banano network
+///
+/// [Nano documentation](https://docs.nano.org/integration-guides/the-basics/#block-format)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename = "state")]
+pub struct StateBlock {
+    pub account: Address,
+    /// Hash of the previous block of the account, [BlockHash::zero] for an open block
+    pub previous: BlockHash,
+    pub representative: Address,
+    pub balance: Raw,
+    /// Source block hash for a receive, destination public key for a send, zero for a change
+    pub link: Link,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<Signature>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub work: Option<Work>,
+}
+
+impl StateBlock {
+    /// Compute the hash of the block, which is what gets signed
+    pub fn hash(&self) -> crate::Result<BlockHash> {
+        let mut preamble = [0u8; 32];
+        preamble[31] = BlockType::State.as_u8();
+
+        let mut bytes = Vec::with_capacity(176);
+        bytes.extend_from_slice(&preamble);
+        bytes.extend_from_slice(&self.account.to_public_key()?.0);
+        bytes.extend_from_slice(self.previous.as_bytes());
+        bytes.extend_from_slice(&self.representative.to_public_key()?.0);
+        bytes.extend_from_slice(&self.balance.to_vec());
+        bytes.extend_from_slice(self.link.as_bytes());
+
+        BlockHash::try_from(&*blake2b(BlockHash::LEN, &bytes))
+    }
+
+    /// Root of the block, used for proof of work: the previous block hash or,
+    /// for an open block, the public key of the account
+    pub fn root(&self) -> crate::Result<BlockHash> {
+        if self.is_open() {
+            Ok(BlockHash(self.account.to_public_key()?.0))
+        } else {
+            Ok(self.previous.clone())
+        }
+    }
+
+    /// Whether this block is the first block of the account chain
+    pub fn is_open(&self) -> bool {
+        self.previous == BlockHash::zero()
+    }
+
+    /// Sign the block with `key`, replacing any existing signature
+    pub fn sign(&mut self, key: &PrivateKey) -> crate::Result<()> {
+        let hash = self.hash()?;
+        self.signature = Some(key.sign(hash.as_bytes()));
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum Previous {
     Block(BlockHash),
@@ -89,3 +188,17 @@ impl FromStr for Previous {
         }
     }
 }
+
+/// Unsigned block without work, shared by tests
+#[cfg(test)]
+pub(crate) fn sample_block() -> StateBlock {
+    StateBlock {
+        account: Address("ban_3iwi45me3cgo9aza9wx5f7rder37hw11xtc1ek8psqxw5oxb8cujjad6qp9y".into()),
+        previous: BlockHash::from_str("40DB7EC1F71F7B3B66982007F20E687148BDB875E533121259C0BF69AEFE88D3").unwrap(),
+        representative: Address("ban_3t6k35gi95xu6tergt6p69ck76ogmitsa8mnijtpxm9fkcm736xtoncuohr3".into()),
+        balance: crate::units::Banano::new(99).to_raw().unwrap(),
+        link: Link::from_str("E89208DD038FBB269987689621D52292AE9C35941A7484756ECCED92A65093BA").unwrap(),
+        signature: None,
+        work: None,
+    }
+}
@@ -7,11 +7,15 @@ use blake2::{
     digest::{Update, VariableOutput},
 };
 pub use self::seed::Seed;
-pub use self::block::BlockHash;
+pub use self::block::{BlockHash, BlockType, BlockSubtype, Link, Previous, QualifiedRoot, Signature, StateBlock};
 pub use self::address::Address;
 pub use self::privkey::PrivateKey;
 pub use self::pubkey::PublicKey;
 pub use self::account::Account;
+pub use self::work::{Difficulty, Work};
+pub use self::wallet::WalletId;
+#[cfg(test)]
+pub(crate) use self::block::sample_block;
 
 mod seed;
 mod block;
@@ -19,6 +23,8 @@ mod address;
 mod privkey;
 mod pubkey;
 mod account;
+mod work;
+mod wallet;
 
 const BAN_ENCODING: Encoding = new_encoding! {
 	symbols: "13456789abcdefghijkmnopqrstuwxyz",
@@ -39,6 +45,7 @@ pub(crate) fn compute_address_checksum(key_bytes: &[u8]) -> [u8; 5] {
 mod tests {
     use data_encoding::HEXLOWER_PERMISSIVE;
     use super::*;
+    use std::str::FromStr;
 
     #[test]
 	fn can_generate_address_from_seed() {
@@ -103,4 +110,37 @@ mod tests {
 		assert_eq!(output, vec![true, true, true, true, true])
 	}
 
+	#[test]
+	fn can_hash_and_sign_state_block() {
+		let seed = Seed::from("1234567890123456789012345678901234567890123456789012345678901234").unwrap();
+		let priv_key = PrivateKey::from_seed(seed, 0);
+		let mut block = sample_block();
+
+		let hash = block.hash().unwrap();
+		assert_eq!(hash.as_hex(), "C251CB179C2267120710E79243159172D51F96B49865C391E41AA3694BF40835");
+
+		block.sign(&priv_key).unwrap();
+		let signature = block.signature.clone().unwrap();
+		assert_eq!(signature.as_hex(), "CC1A64F14790A4A9EBC2AFEC2E1D0446211027E180E7597AADEE778BB67A8659A057C165BA006DC7B8B4D59EA264B4222800ED2925FE889FD3F2234C57D08F04");
+
+		let public_key = PublicKey::from(&priv_key);
+		assert!(public_key.verify(hash.as_bytes(), &signature));
+		assert!(!public_key.verify(BlockHash::zero().as_bytes(), &signature));
+	}
+
+	#[test]
+	fn can_serialize_state_block() {
+		let mut block = sample_block();
+		block.work = Some(Work::from_str("2BF29EF00786A6BC").unwrap());
+		let json = serde_json::to_value(&block).unwrap();
+		assert_eq!(json["type"], "state");
+		assert_eq!(json["balance"], "9900000000000000000000000000000");
+		assert_eq!(json["link"], "E89208DD038FBB269987689621D52292AE9C35941A7484756ECCED92A65093BA");
+		assert_eq!(json["work"], "2BF29EF00786A6BC");
+		assert!(json.get("signature").is_none());
+
+		let decoded: StateBlock = serde_json::from_value(json).unwrap();
+		assert_eq!(decoded, block);
+	}
+
 }
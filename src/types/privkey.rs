@@ -1,10 +1,13 @@
-use super::Seed;
+use super::{Seed, Signature};
 use ed25519_dalek::SecretKey;
 use blake2::{
+    Blake2b, Digest,
     VarBlake2b,
     digest::{Update, VariableOutput},
 };
 use byteorder::{BigEndian, WriteBytesExt};
+use curve25519_dalek::constants::ED25519_BASEPOINT_TABLE;
+use curve25519_dalek::scalar::Scalar as CurveScaler;
 use std::ops::{Deref, DerefMut};
 
 /// Banano PrivateKey
@@ -16,13 +19,56 @@ impl PrivateKey {
 		let mut blake = VarBlake2b::new(32).unwrap();
 		let mut index_buf = Vec::with_capacity(4);
 		index_buf.write_u32::<BigEndian>(index).unwrap();
-		blake.update(&*seed);
+		blake.update(*seed);
 		blake.update(&index_buf);
 
 		let mut buf = [0u8; 32];
         blake.finalize_variable(|res| buf.copy_from_slice(res));
 		PrivateKey(SecretKey::from_bytes(&buf).unwrap())
 	}
+
+	/// Expand the key into its signing scalar and nonce prefix, using Blake2b instead of SHA-512
+	pub(crate) fn expand(&self) -> (CurveScaler, [u8; 32]) {
+		let hash = Blake2b::digest(self.0.as_bytes());
+		let mut scaler = [0u8; 32];
+		scaler.copy_from_slice(&hash[..32]);
+		scaler[0] &= 248;
+		scaler[31] &= 63;
+		scaler[31] |= 64;
+		let mut prefix = [0u8; 32];
+		prefix.copy_from_slice(&hash[32..]);
+		(CurveScaler::from_bits(scaler), prefix)
+	}
+
+	/// Sign `message` (usually a block hash) using Ed25519 with Blake2b, as done by Banano nodes
+	pub fn sign(&self, message: &[u8]) -> Signature {
+		let (scaler, prefix) = self.expand();
+		let public_key = (&scaler * &ED25519_BASEPOINT_TABLE).compress();
+
+		let r = CurveScaler::from_bytes_mod_order_wide(&wide_hash(&[&prefix, message]));
+		let big_r = (&r * &ED25519_BASEPOINT_TABLE).compress();
+		let k = CurveScaler::from_bytes_mod_order_wide(&wide_hash(&[big_r.as_bytes(), public_key.as_bytes(), message]));
+		let s = k * scaler + r;
+
+		let mut signature = [0u8; 64];
+		signature[..32].copy_from_slice(big_r.as_bytes());
+		signature[32..].copy_from_slice(s.as_bytes());
+		Signature::from(signature)
+	}
+}
+
+pub(crate) fn wide_hash(parts: &[&[u8]]) -> [u8; 64] {
+	let mut hasher = Blake2b::default();
+	parts.iter().for_each(|part| Digest::update(&mut hasher, part));
+	let mut hash = [0u8; 64];
+	hash.copy_from_slice(&hasher.finalize());
+	hash
+}
+
+impl Clone for PrivateKey {
+	fn clone(&self) -> Self {
+		PrivateKey(SecretKey::from_bytes(self.0.as_bytes()).unwrap())
+	}
 }
 
 impl Deref for PrivateKey {
@@ -42,4 +88,4 @@ impl From<PrivateKey> for [u8; 32] {
 	fn from(key: PrivateKey) ->  Self {
 		key.0.to_bytes()
 	}
-}
\ No newline at end of file
+}
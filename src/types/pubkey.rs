@@ -1,25 +1,42 @@
-use super::privkey::PrivateKey;
-use blake2::{Blake2b, Digest};
+use super::privkey::{PrivateKey, wide_hash};
+use super::Signature;
 use curve25519_dalek::constants::ED25519_BASEPOINT_TABLE;
+use curve25519_dalek::edwards::{CompressedEdwardsY, EdwardsPoint};
 use curve25519_dalek::scalar::Scalar as CurveScaler;
+use std::convert::TryInto;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct PublicKey(pub [u8; 32]);
 
+impl PublicKey {
+	/// Check that `signature` was made by the private key matching this public key
+	pub fn verify(&self, message: &[u8], signature: &Signature) -> bool {
+		let signature = signature.as_bytes();
+		let point = match CompressedEdwardsY(self.0).decompress() {
+			Some(point) => point,
+			None => return false,
+		};
+		let s = match CurveScaler::from_canonical_bytes(signature[32..].try_into().unwrap()) {
+			Some(s) => s,
+			None => return false,
+		};
+		let k = CurveScaler::from_bytes_mod_order_wide(&wide_hash(&[&signature[..32], &self.0, message]));
+		let big_r = EdwardsPoint::vartime_double_scalar_mul_basepoint(&k, &-point, &s);
+		big_r.compress().as_bytes() == &signature[..32]
+	}
+}
+
+impl From<&PrivateKey> for PublicKey {
+	fn from(key: &PrivateKey) -> Self {
+		let (scaler, _) = key.expand();
+		let point = &scaler * &ED25519_BASEPOINT_TABLE;
+		PublicKey(point.compress().to_bytes())
+	}
+}
+
 impl From<PrivateKey> for PublicKey {
 	fn from(key: PrivateKey) -> Self {
-		let mut bhasher = Blake2b::default();
-		let bkey: [u8; 32] = key.into();
-		bhasher.update(&bkey);
-		let hresult = bhasher.finalize();
-		let mut scaler = [0u8; 32];
-		scaler.copy_from_slice(&hresult.as_slice()[..32]);
-		scaler[0] &= 248;
-		scaler[31] &= 63;
-		scaler[31] |= 64;
-		let cscaler = CurveScaler::from_bits(scaler);
-		let point = &cscaler * &ED25519_BASEPOINT_TABLE;
-		PublicKey(point.compress().to_bytes())
+		PublicKey::from(&key)
 	}
 }
 
@@ -13,7 +13,7 @@ impl Seed {
 			return Err(Error::SeedLengthError(seed.len()));
 		}
 
-		let seed = HEXUPPER_PERMISSIVE.decode(&seed).unwrap();
+		let seed = HEXUPPER_PERMISSIVE.decode(seed).unwrap();
 		let mut seed_bytes = [0u8; 32];
 		seed_bytes.copy_from_slice(&seed);
 		Ok(Seed(seed_bytes))
@@ -0,0 +1,4 @@
+use crate::hexify;
+use std::str::FromStr;
+
+hexify!(WalletId, 32, "Identifier of a wallet stored by a node", "000D1BAEC8EC208142C99059B393051BAC8380F9B5A2E6B2489A277D81789F3F");
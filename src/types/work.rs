@@ -0,0 +1,140 @@
+use crate::{hexify, Error, encoding::blake2b};
+use super::BlockHash;
+use serde::{Serialize, Deserialize, Serializer, Deserializer, de};
+use std::{convert::TryInto, fmt::Display, str::FromStr};
+
+hexify!(Work, 8, "Proof of work", "2BF29EF00786A6BC");
+
+impl Work {
+    /// Compute the difficulty reached by this work for `root`
+    pub fn difficulty(&self, root: &BlockHash) -> Difficulty {
+        let mut work = self.0;
+        work.reverse();
+        Difficulty::from_hash(&work, root)
+    }
+
+    /// Returns `true` when this work reaches `threshold` for `root`
+    pub fn is_valid(&self, root: &BlockHash, threshold: &Difficulty) -> bool {
+        self.difficulty(root) >= *threshold
+    }
+
+    /// Compute work for `root` on the CPU, blocking until `threshold` is reached.
+    ///
+    /// This is slow: prefer a work server for anything else than occasional blocks.
+    pub fn generate(root: &BlockHash, threshold: &Difficulty) -> Work {
+        let mut nonce = u64::from_le_bytes(root.as_bytes()[..8].try_into().unwrap());
+        loop {
+            let work = nonce.to_le_bytes();
+            if Difficulty::from_hash(&work, root) >= *threshold {
+                return Work(nonce.to_be_bytes());
+            }
+            nonce = nonce.wrapping_add(1);
+        }
+    }
+}
+
+/// Proof of work difficulty, serialized as a 16 characters hex string
+///
+/// ```
+/// use banano_rs::types::Difficulty;
+/// use std::str::FromStr;
+///
+/// let difficulty = Difficulty::from_str("fffffe0000000000").unwrap();
+/// assert_eq!(difficulty, Difficulty::DEFAULT);
+/// assert_eq!(Difficulty::DEFAULT.from_multiplier(2.0).to_string(), "ffffff0000000000");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Difficulty(pub u64);
+
+impl Difficulty {
+    /// Threshold required by the Banano network for every block
+    pub const DEFAULT: Difficulty = Difficulty(0xfffffe0000000000);
+
+    fn from_hash(work: &[u8; 8], root: &BlockHash) -> Difficulty {
+        let mut bytes = Vec::with_capacity(40);
+        bytes.extend_from_slice(work);
+        bytes.extend_from_slice(root.as_bytes());
+        let hash = blake2b(8, &bytes);
+        Difficulty(u64::from_le_bytes((*hash).try_into().unwrap()))
+    }
+
+    /// Difficulty `multiplier` times harder than this one
+    pub fn from_multiplier(&self, multiplier: f64) -> Difficulty {
+        let reverse = self.0.wrapping_neg() as f64 / multiplier;
+        Difficulty((reverse as u64).wrapping_neg())
+    }
+
+    /// How many times this difficulty is harder than `base`
+    pub fn multiplier(&self, base: &Difficulty) -> f64 {
+        base.0.wrapping_neg() as f64 / self.0.wrapping_neg() as f64
+    }
+}
+
+impl Default for Difficulty {
+    fn default() -> Self {
+        Difficulty::DEFAULT
+    }
+}
+
+impl Display for Difficulty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Difficulty(u64::from_str_radix(s, 16)?))
+    }
+}
+
+impl Serialize for Difficulty {
+    fn serialize<S>(&self, serializer: S) -> Result<<S as Serializer>::Ok, <S as Serializer>::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Difficulty {
+    fn deserialize<D>(deserializer: D) -> Result<Self, <D as Deserializer<'de>>::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s: String = Deserialize::deserialize(deserializer)?;
+        Difficulty::from_str(&s).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn difficulty_of_work() {
+        let root = BlockHash::from_str("718CC2121C3E641059BC1C2CFC45666C99E8AE922F7A807B7D07B62C995D79E2").unwrap();
+        let work = Work::from_str("2BF29EF00786A6BC").unwrap();
+        assert_eq!(work.difficulty(&root), Difficulty(0xffffffd21c3933f4));
+        assert!(work.is_valid(&root, &Difficulty::DEFAULT));
+        assert!(!work.is_valid(&root, &Difficulty(0xffffffe000000000)));
+    }
+
+    #[test]
+    fn generate_work() {
+        let root = BlockHash::from_str("718CC2121C3E641059BC1C2CFC45666C99E8AE922F7A807B7D07B62C995D79E2").unwrap();
+        let threshold = Difficulty(0xff00000000000000);
+        let work = Work::generate(&root, &threshold);
+        assert!(work.is_valid(&root, &threshold));
+    }
+
+    #[test]
+    fn multiplier() {
+        let base = Difficulty::DEFAULT;
+        assert_eq!(base.from_multiplier(1.0), base);
+        assert_eq!(base.from_multiplier(8.0).multiplier(&base), 8.0);
+        assert_eq!(serde_json::to_string(&base).unwrap(), r#""fffffe0000000000""#);
+    }
+}
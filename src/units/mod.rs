@@ -87,7 +87,7 @@ use bigdecimal::BigDecimal;
 use bigdecimal::ToPrimitive;
 use doc_comment::doc_comment;
 use once_cell::sync::Lazy;
-pub use raw::Raw;
+pub use raw::{deserialize_from_hex, serialize_to_hex, Raw};
 use std::convert::TryFrom;
 use std::str::FromStr;
 
@@ -205,9 +205,9 @@ See the [module documentation](crate::units) for more information as this is gen
             }
         }
 
-        impl ToString for $struct_name {
-            fn to_string(&self) -> String {
-                self.0.to_string()
+        impl std::fmt::Display for $struct_name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "{}", self.0)
             }
         }
 
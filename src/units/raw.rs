@@ -41,7 +41,7 @@ impl Raw {
             msg: String::from("Decoding hex raw"),
             source: e,
         })?;
-        Ok(Raw::try_from(vec.as_slice())?)
+        Raw::try_from(vec.as_slice())
     }
 
     pub fn zero() -> Self {
@@ -114,8 +114,8 @@ impl<'de> Deserialize<'de> for Raw {
     where
         D: Deserializer<'de>,
     {
-        let s: &str = Deserialize::deserialize(deserializer)?;
-        Ok(Raw::from_str(s).map_err(de::Error::custom)?)
+        let s: String = Deserialize::deserialize(deserializer)?;
+        Raw::from_str(&s).map_err(de::Error::custom)
     }
 }
 
@@ -133,8 +133,8 @@ pub fn deserialize_from_hex<'de, D>(deserializer: D) -> Result<Raw, <D as Deseri
 where
     D: Deserializer<'de>,
 {
-    let s: &str = Deserialize::deserialize(deserializer)?;
-    Ok(Raw::from_hex(s).map_err(de::Error::custom)?)
+    let s: String = Deserialize::deserialize(deserializer)?;
+    Raw::from_hex(&s).map_err(de::Error::custom)
 }
 
 impl Display for Raw {
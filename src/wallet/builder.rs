@@ -0,0 +1,68 @@
+use crate::{Address, BananoApi, Error, api::{LocalWorkGenerator, ProcessOptions, WorkGenerator}, types::{Account, BlockHash, BlockSubtype, Difficulty, PrivateKey, StateBlock}};
+use super::{AccountState, complete};
+use std::sync::Arc;
+
+/// Part shared by the block builders: the signing account, its state and how work is obtained
+pub(crate) struct BlockBuilder {
+    pub(crate) api: BananoApi,
+    pub(crate) key: PrivateKey,
+    pub(crate) account: Address,
+    pub(crate) state: Option<AccountState>,
+    pub(crate) work_generator: Arc<dyn WorkGenerator>,
+    pub(crate) work_threshold: Difficulty,
+}
+
+impl BlockBuilder {
+    pub(crate) fn new(api: BananoApi, key: PrivateKey) -> Self {
+        BlockBuilder {
+            api,
+            account: Account::from(key.clone()).address,
+            key,
+            state: None,
+            work_generator: Arc::new(LocalWorkGenerator),
+            work_threshold: Difficulty::DEFAULT,
+        }
+    }
+
+    /// Signed block made by `next_block` from the state of the account, fetched unless known
+    pub(crate) async fn build<F>(&self, next_block: F) -> Result<StateBlock, Error>
+    where
+        F: FnOnce(&AccountState) -> Result<StateBlock, Error>,
+    {
+        let state = match &self.state {
+            Some(state) => state.clone(),
+            None => AccountState::fetch(&self.api, &self.account).await?,
+        };
+        let block = next_block(&state)?;
+        complete(block, &self.key, &*self.work_generator, self.work_threshold).await
+    }
+
+    /// Publish `block` as `subtype`, a receive block without previous one being an open block
+    pub(crate) async fn publish(&self, block: &StateBlock, subtype: BlockSubtype) -> Result<BlockHash, Error> {
+        let subtype = if subtype == BlockSubtype::Receive && block.is_open() { BlockSubtype::Open } else { subtype };
+        self.api.process(block, ProcessOptions { subtype: Some(subtype), ..Default::default() }).await
+    }
+}
+
+/// Setters of a builder keeping its [BlockBuilder] in a `base` field
+macro_rules! block_builder_setters {
+    () => {
+        /// Build on top of an already known `state` of the account instead of fetching it
+        pub fn state(mut self, state: $crate::wallet::AccountState) -> Self {
+            self.base.state = Some(state);
+            self
+        }
+
+        /// Obtain work from `work_generator`, like a node or a [WorkClient](crate::api::WorkClient)
+        pub fn work_generator<W: $crate::api::WorkGenerator + 'static>(mut self, work_generator: W) -> Self {
+            self.base.work_generator = std::sync::Arc::new(work_generator);
+            self
+        }
+
+        /// Generate work reaching `threshold` instead of [Difficulty::DEFAULT](crate::types::Difficulty::DEFAULT)
+        pub fn work_threshold(mut self, threshold: $crate::types::Difficulty) -> Self {
+            self.base.work_threshold = threshold;
+            self
+        }
+    };
+}
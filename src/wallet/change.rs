@@ -0,0 +1,277 @@
+use crate::{Address, BananoApi, Error, types::{BlockHash, BlockSubtype, Link, PrivateKey, StateBlock}};
+use super::{AccountState, Wallet, builder::BlockBuilder};
+use futures::{stream, Stream, StreamExt};
+use log::debug;
+use std::collections::HashSet;
+
+/// Builder for a change block, delegating the account of a [PrivateKey] to a new representative
+///
+/// The state of the account is fetched with `account_info` unless already known, see
+/// [state](ChangeBuilder::state). Only opened accounts can change their representative.
+///
+/// # Example:
+/// ```no_run
+/// use banano_rs::{Address, BananoApi, types::{PrivateKey, Seed}, wallet::ChangeBuilder};
+///
+/// # async fn run() -> banano_rs::Result<()> {
+/// let banano = BananoApi::new("https://kaliumapi.appditto.com/api".into());
+/// let key = PrivateKey::from_seed(Seed::from("1234567890123456789012345678901234567890123456789012345678901234")?, 0);
+/// let representative = Address("ban_1fomoz167m7o38gw4rzt7hz67oq6itejpt4yocrfywujbpatd711cjew8gjj".into());
+/// let hash = ChangeBuilder::new(banano, key, representative).publish().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct ChangeBuilder {
+    base: BlockBuilder,
+    representative: Address,
+}
+
+impl ChangeBuilder {
+    /// Delegate the account of `key` to `representative`
+    pub fn new(api: BananoApi, key: PrivateKey, representative: Address) -> Self {
+        ChangeBuilder {
+            base: BlockBuilder::new(api, key),
+            representative,
+        }
+    }
+
+    block_builder_setters!();
+
+    /// Unsigned change block following `state`
+    pub(crate) fn next_block(&self, state: &AccountState) -> Result<StateBlock, Error> {
+        if !state.is_opened() {
+            return Err(Error::AccountNotOpened { address: self.base.account.clone() });
+        }
+        Ok(state.next_block(&self.base.account, state.balance.clone(), self.representative.clone(), Link::from(BlockHash::zero())))
+    }
+
+    /// Signed change block, with its work, ready to be published
+    pub async fn build(self) -> Result<StateBlock, Error> {
+        self.base.build(|state| self.next_block(state)).await
+    }
+
+    /// Build the change block and publish it, returning its hash
+    pub async fn publish(self) -> Result<BlockHash, Error> {
+        let block = self.base.build(|state| self.next_block(state)).await?;
+        self.base.publish(&block, BlockSubtype::Change).await
+    }
+}
+
+/// What a [Redelegation] did for an account
+#[derive(Debug, Clone, PartialEq)]
+pub enum Redelegated {
+    /// Hash of the change block
+    Changed(BlockHash),
+    /// The account was already delegated to the representative
+    AlreadyDelegated,
+    /// The account has no block yet, it gets a representative when opened
+    NotOpened,
+}
+
+/// Outcome for one account of a [Redelegation]
+#[derive(Debug)]
+pub struct RedelegationProgress {
+    pub account: Address,
+    pub result: Result<Redelegated, Error>,
+    /// Accounts handled so far, failed or not, including those completed by a previous run
+    pub completed: usize,
+    /// Accounts handled without error so far, including those completed by a previous run
+    pub succeeded: usize,
+    pub total: usize,
+}
+
+/// Moves many accounts of a [Wallet] to a new representative, see [Wallet::redelegate]
+///
+/// Accounts are handled concurrently and reported one by one as they are done. A redelegation
+/// can be resumed by giving the accounts reported without error to
+/// [completed](Redelegation::completed); accounts already delegated to the representative are
+/// skipped anyway, without publishing any block.
+///
+/// # Example:
+/// ```no_run
+/// use banano_rs::{Address, BananoApi, types::Seed, wallet::Wallet};
+/// use futures::StreamExt;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), banano_rs::Error> {
+///     let banano = BananoApi::new("https://kaliumapi.appditto.com/api".into());
+///     let seed = Seed::from("1234567890123456789012345678901234567890123456789012345678901234")?;
+///     let mut wallet = Wallet::from_seed(banano, seed);
+///     for index in 0..500 {
+///         wallet.derive(index)?;
+///     }
+///     let representative = Address("ban_1fomoz167m7o38gw4rzt7hz67oq6itejpt4yocrfywujbpatd711cjew8gjj".into());
+///     let mut progress = Box::pin(wallet.redelegate(representative).concurrency(8).run());
+///     while let Some(progress) = progress.next().await {
+///         if let Err(error) = progress.result {
+///             eprintln!("{} failed: {}", progress.account.0, error);
+///         }
+///         println!("{}/{}", progress.completed, progress.total);
+///     }
+///     Ok(())
+/// }
+/// ```
+pub struct Redelegation<'a> {
+    wallet: &'a Wallet,
+    representative: Address,
+    accounts: Vec<Address>,
+    completed: HashSet<Address>,
+    concurrency: usize,
+}
+
+impl<'a> Redelegation<'a> {
+    /// Only move `accounts` instead of every account of the wallet
+    pub fn accounts(mut self, accounts: Vec<Address>) -> Self {
+        self.accounts = accounts;
+        self
+    }
+
+    /// Skip `accounts`, already moved by a previous run
+    pub fn completed<I: IntoIterator<Item = Address>>(mut self, accounts: I) -> Self {
+        self.completed.extend(accounts);
+        self
+    }
+
+    /// How many accounts are handled at once, 4 by default
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Move the accounts, reporting progress after each of them
+    pub fn run(self) -> impl Stream<Item = RedelegationProgress> + 'a {
+        let total = self.accounts.len();
+        let done = self.completed;
+        let (completed, remaining): (Vec<Address>, Vec<Address>) = self
+            .accounts
+            .into_iter()
+            .partition(|account| done.contains(account));
+        let wallet = self.wallet;
+        let representative = self.representative;
+        stream::iter(remaining)
+            .map(move |account| {
+                let representative = representative.clone();
+                async move {
+                    let result = wallet.redelegate_account(&account, &representative).await;
+                    (account, result)
+                }
+            })
+            .buffer_unordered(self.concurrency)
+            .scan((completed.len(), completed.len()), move |(completed, succeeded), (account, result)| {
+                *completed += 1;
+                *succeeded += result.is_ok() as usize;
+                futures::future::ready(Some(RedelegationProgress {
+                    account,
+                    result,
+                    completed: *completed,
+                    succeeded: *succeeded,
+                    total,
+                }))
+            })
+    }
+}
+
+impl Wallet {
+    /// Move every account of the wallet to `representative`, see [Redelegation]
+    pub fn redelegate(&self, representative: Address) -> Redelegation<'_> {
+        Redelegation {
+            wallet: self,
+            representative,
+            accounts: self.addresses(),
+            completed: HashSet::new(),
+            concurrency: 4,
+        }
+    }
+
+    async fn redelegate_account(&self, account: &Address, representative: &Address) -> Result<Redelegated, Error> {
+        // the cached state may predate blocks published elsewhere
+        let state = self.refresh(account).await?;
+        if !state.is_opened() {
+            return Ok(Redelegated::NotOpened);
+        }
+        if state.representative.as_ref() == Some(representative) {
+            return Ok(Redelegated::AlreadyDelegated);
+        }
+        let hash = self.change_representative(account, representative).await?;
+        debug!("Delegated {} to {}", account.0, representative.0);
+        Ok(Redelegated::Changed(hash))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{api::MockNode, types::{Account, Seed}, units::Banano};
+    use std::collections::HashMap;
+
+    #[tokio::test]
+    async fn publishes_change_blocks() {
+        let node = MockNode::new().with_work_threshold(MockNode::low_work_threshold());
+        let key = PrivateKey::from_seed(Seed([5; 32]), 0);
+        let account = Account::from(key.clone()).address;
+        let representative = Address(MockNode::REPRESENTATIVE.into());
+
+        let error = ChangeBuilder::new(node.api(), key.clone(), representative.clone()).publish().await.unwrap_err();
+        assert!(matches!(error, Error::AccountNotOpened { .. }));
+
+        node.add_account(&account, Banano::new(1).to_raw().unwrap(), &account).unwrap();
+        let hash = ChangeBuilder::new(node.api(), key, representative.clone())
+            .work_threshold(MockNode::low_work_threshold())
+            .publish()
+            .await
+            .unwrap();
+        let state = node.account_state(&account).unwrap();
+        assert_eq!(state.representative_block, hash);
+        assert_eq!(state.representative, representative);
+        assert_eq!(state.balance, Banano::new(1).to_raw().unwrap());
+    }
+
+    #[tokio::test]
+    async fn redelegates_accounts_and_resumes() {
+        let node = MockNode::new().with_work_threshold(MockNode::low_work_threshold());
+        let mut wallet = Wallet::from_seed(node.api(), Seed([5; 32])).with_work_threshold(MockNode::low_work_threshold());
+        let accounts: Vec<Address> = (0..4).map(|index| wallet.derive(index).unwrap().address.clone()).collect();
+        let old = Address(MockNode::ACCOUNT.into());
+        let new = Address(MockNode::REPRESENTATIVE.into());
+        // cached before the account is opened
+        assert!(!wallet.state(&accounts[0]).await.unwrap().is_opened());
+        node.add_account(&accounts[0], Banano::new(1).to_raw().unwrap(), &old).unwrap();
+        node.add_account(&accounts[1], Banano::new(1).to_raw().unwrap(), &new).unwrap();
+        node.add_account(&accounts[3], Banano::new(1).to_raw().unwrap(), &old).unwrap();
+
+        let progress: Vec<RedelegationProgress> = wallet
+            .redelegate(new.clone())
+            .completed(vec![accounts[3].clone()])
+            .run()
+            .collect()
+            .await;
+        assert_eq!(progress.iter().map(|progress| progress.completed).collect::<Vec<_>>(), vec![2, 3, 4]);
+        assert_eq!(progress.iter().map(|progress| progress.succeeded).collect::<Vec<_>>(), vec![2, 3, 4]);
+        assert!(progress.iter().all(|progress| progress.total == 4));
+        let results: HashMap<Address, Redelegated> = progress
+            .into_iter()
+            .map(|progress| (progress.account, progress.result.unwrap()))
+            .collect();
+        assert!(matches!(results[&accounts[0]], Redelegated::Changed(_)));
+        assert_eq!(results[&accounts[1]], Redelegated::AlreadyDelegated);
+        assert_eq!(results[&accounts[2]], Redelegated::NotOpened);
+        assert!(!results.contains_key(&accounts[3]));
+        assert_eq!(node.account_state(&accounts[0]).unwrap().representative, new);
+        assert_eq!(node.account_state(&accounts[3]).unwrap().representative, old);
+
+        // running again only publishes blocks for accounts not moved yet
+        let progress: Vec<RedelegationProgress> = wallet.redelegate(new.clone()).run().collect().await;
+        let changed: Vec<&Address> = progress
+            .iter()
+            .filter(|progress| matches!(progress.result, Ok(Redelegated::Changed(_))))
+            .map(|progress| &progress.account)
+            .collect();
+        assert_eq!(changed, vec![&accounts[3]]);
+        assert_eq!(node.requests_for("process").len(), 2);
+
+        // failures are completed without succeeding
+        let progress: Vec<RedelegationProgress> = wallet.redelegate(new).accounts(vec![old]).run().collect().await;
+        assert!(matches!(progress[0].result, Err(Error::AccountNotInWallet { .. })));
+        assert_eq!((progress[0].completed, progress[0].succeeded), (1, 0));
+    }
+}
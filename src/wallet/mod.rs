@@ -0,0 +1,362 @@
+//! Local wallet, signing blocks with its own keys instead of relying on a node wallet
+//!
+//! # Example:
+//! ```no_run
+//! use banano_rs::{Address, BananoApi, types::Seed, units::Banano, wallet::Wallet};
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), banano_rs::Error> {
+//!     let banano = BananoApi::new("https://kaliumapi.appditto.com/api".into());
+//!     let seed = Seed::from("1234567890123456789012345678901234567890123456789012345678901234")?;
+//!     let mut wallet = Wallet::from_seed(banano, seed);
+//!     let account = wallet.derive(0)?.address.clone();
+//!     let destination = Address("ban_1hgtqu7cmgxb66ta4gxt7coimqcxp86nzi5b7u14ip9zzpqr16a3dbqdja1f".into());
+//!     let hash = wallet.send(&account, &destination, Banano::new(1).to_raw()?).await?;
+//!     println!("sent 1 BAN in {}", hash);
+//!     Ok(())
+//! }
+//! ```
+
+use crate::{Address, BananoApi, Error, Raw, api::{LocalWorkGenerator, ProcessOptions, WorkGenerator}, types::{Account, BlockHash, BlockSubtype, Difficulty, Link, PrivateKey, Seed, StateBlock}};
+use futures::lock::Mutex;
+use log::debug;
+use std::{collections::HashMap, sync::Arc};
+pub use self::change::{ChangeBuilder, Redelegated, Redelegation, RedelegationProgress};
+pub use self::receive::{AutoReceiver, ReceiveBuilder, ReceiveResult, Received};
+pub use self::send::SendBuilder;
+
+#[macro_use]
+mod builder;
+mod change;
+mod receive;
+mod send;
+
+/// State of an account chain, as of its frontier
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccountState {
+    /// Last block of the account, [BlockHash::zero] if the account is not opened yet
+    pub frontier: BlockHash,
+    pub balance: Raw,
+    /// `None` if the account is not opened yet
+    pub representative: Option<Address>,
+}
+
+impl AccountState {
+    /// State of an account without any block
+    pub fn unopened() -> Self {
+        AccountState {
+            frontier: BlockHash::zero(),
+            balance: Raw::zero(),
+            representative: None,
+        }
+    }
+
+    /// Whether the account has at least one block
+    pub fn is_opened(&self) -> bool {
+        self.frontier != BlockHash::zero()
+    }
+
+    /// Fetch the state of `account` from the node
+    pub async fn fetch(api: &BananoApi, account: &Address) -> Result<Self, Error> {
+        match api.account_info(account).await {
+            Ok(info) => Ok(AccountState {
+                frontier: info.frontier,
+                balance: info.balance,
+                representative: info.representative,
+            }),
+            Err(Error::NodeError(error)) if error == "Account not found" => Ok(AccountState::unopened()),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Unsigned block of `account` following this state
+    pub(crate) fn next_block(&self, account: &Address, balance: Raw, representative: Address, link: Link) -> StateBlock {
+        StateBlock {
+            account: account.clone(),
+            previous: self.frontier.clone(),
+            representative,
+            balance,
+            link,
+            signature: None,
+            work: None,
+        }
+    }
+}
+
+/// Account of a [Wallet], with its key and the cached state of its chain
+pub struct WalletAccount {
+    pub account: Account,
+    /// Index of the account in the seed, `None` for an imported key
+    pub index: Option<u32>,
+    key: PrivateKey,
+    /// Held for the whole duration of an operation, so that blocks of an account are built one at a time
+    state: Mutex<Option<AccountState>>,
+}
+
+/// Wallet holding private keys, which builds, signs and publishes blocks itself
+///
+/// The frontier, balance and representative of every account are cached after their first use
+/// and updated with every published block, so the wallet is expected to be the only one
+/// publishing blocks for its accounts. The cache of an account is dropped whenever publishing
+/// fails, and can be reloaded at any time with [refresh](Wallet::refresh).
+///
+/// Work is generated on the CPU unless another [WorkGenerator] is set.
+pub struct Wallet {
+    api: BananoApi,
+    seed: Option<Seed>,
+    accounts: Vec<WalletAccount>,
+    indices: HashMap<Address, usize>,
+    work_generator: Arc<dyn WorkGenerator>,
+    work_threshold: Difficulty,
+    representative: Option<Address>,
+}
+
+impl Wallet {
+    /// Wallet without any account, see [import](Wallet::import)
+    pub fn new(api: BananoApi) -> Self {
+        Wallet {
+            api,
+            seed: None,
+            accounts: Vec::new(),
+            indices: HashMap::new(),
+            work_generator: Arc::new(LocalWorkGenerator),
+            work_threshold: Difficulty::DEFAULT,
+            representative: None,
+        }
+    }
+
+    /// Wallet deriving its accounts from `seed`, see [derive](Wallet::derive)
+    pub fn from_seed(api: BananoApi, seed: Seed) -> Self {
+        Wallet {
+            seed: Some(seed),
+            ..Self::new(api)
+        }
+    }
+
+    /// Obtain work from `work_generator`, like a node or a [WorkClient](crate::api::WorkClient)
+    pub fn with_work_generator<W: WorkGenerator + 'static>(mut self, work_generator: W) -> Self {
+        self.work_generator = Arc::new(work_generator);
+        self
+    }
+
+    /// Generate work reaching `threshold` instead of [Difficulty::DEFAULT]
+    pub fn with_work_threshold(mut self, threshold: Difficulty) -> Self {
+        self.work_threshold = threshold;
+        self
+    }
+
+    /// Representative of the accounts opened by this wallet, defaulting to each account itself
+    pub fn with_representative(mut self, representative: Address) -> Self {
+        self.representative = Some(representative);
+        self
+    }
+
+    /// Add the account at `index` of the seed, if not already added
+    pub fn derive(&mut self, index: u32) -> Result<&Account, Error> {
+        let seed = self.seed.clone().ok_or(Error::NoSeed)?;
+        Ok(self.add(PrivateKey::from_seed(seed, index), Some(index)))
+    }
+
+    /// Add the account of `key`, if not already added
+    pub fn import(&mut self, key: PrivateKey) -> &Account {
+        self.add(key, None)
+    }
+
+    fn add(&mut self, key: PrivateKey, index: Option<u32>) -> &Account {
+        let account = Account::from(key.clone());
+        let position = match self.indices.get(&account.address) {
+            Some(position) => *position,
+            None => {
+                self.indices.insert(account.address.clone(), self.accounts.len());
+                self.accounts.push(WalletAccount {
+                    account,
+                    index,
+                    key,
+                    state: Mutex::new(None),
+                });
+                self.accounts.len() - 1
+            }
+        };
+        &self.accounts[position].account
+    }
+
+    /// Accounts of the wallet, in the order they were added
+    pub fn accounts(&self) -> impl Iterator<Item = &WalletAccount> {
+        self.accounts.iter()
+    }
+
+    /// Addresses of the accounts of the wallet, like to watch them with a
+    /// [PaymentWatcher](crate::payments::PaymentWatcher)
+    pub fn addresses(&self) -> Vec<Address> {
+        self.accounts.iter().map(|account| account.account.address.clone()).collect()
+    }
+
+    fn account(&self, address: &Address) -> Result<&WalletAccount, Error> {
+        self.indices
+            .get(address)
+            .map(|position| &self.accounts[*position])
+            .ok_or_else(|| Error::AccountNotInWallet { address: address.clone() })
+    }
+
+    /// Cached state of `account`, fetched from the node on first use
+    pub async fn state(&self, account: &Address) -> Result<AccountState, Error> {
+        let mut state = self.account(account)?.state.lock().await;
+        if state.is_none() {
+            *state = Some(AccountState::fetch(&self.api, account).await?);
+        }
+        Ok(state.clone().unwrap())
+    }
+
+    /// Drop the cached state of `account` and fetch it again from the node
+    pub async fn refresh(&self, account: &Address) -> Result<AccountState, Error> {
+        let mut state = self.account(account)?.state.lock().await;
+        *state = Some(AccountState::fetch(&self.api, account).await?);
+        Ok(state.clone().unwrap())
+    }
+
+    /// Balance of `account`, excluding receivable funds
+    pub async fn balance(&self, account: &Address) -> Result<Raw, Error> {
+        Ok(self.state(account).await?.balance)
+    }
+
+    /// Send `amount` from `account` to `destination`
+    pub async fn send(&self, account: &Address, destination: &Address, amount: Raw) -> Result<BlockHash, Error> {
+        let key = self.account(account)?.key.clone();
+        let builder = SendBuilder::new(self.api.clone(), key, destination.clone(), amount);
+        self.publish_block(account, BlockSubtype::Send, |state| builder.next_block(state)).await
+    }
+
+    /// Receive the funds of the send block `hash`, opening `account` if needed
+    pub async fn receive(&self, account: &Address, hash: &BlockHash) -> Result<BlockHash, Error> {
+        let key = self.account(account)?.key.clone();
+        let mut builder = ReceiveBuilder::new(self.api.clone(), key, hash.clone()).open_representative(self.open_representative(account));
+        builder.fetch_amount().await?;
+        self.publish_block(account, BlockSubtype::Receive, |state| builder.next_block(state)).await
+    }
+
+    /// Receive `amount` from the send block `hash`, opening `account` if needed
+    pub(crate) async fn receive_amount(&self, account: &Address, hash: &BlockHash, amount: Raw) -> Result<BlockHash, Error> {
+        let key = self.account(account)?.key.clone();
+        let builder = ReceiveBuilder::new(self.api.clone(), key, hash.clone())
+            .amount(amount)
+            .open_representative(self.open_representative(account));
+        self.publish_block(account, BlockSubtype::Receive, |state| builder.next_block(state)).await
+    }
+
+    /// Delegate the voting weight of `account` to `representative`
+    pub async fn change_representative(&self, account: &Address, representative: &Address) -> Result<BlockHash, Error> {
+        let key = self.account(account)?.key.clone();
+        let builder = ChangeBuilder::new(self.api.clone(), key, representative.clone());
+        self.publish_block(account, BlockSubtype::Change, |state| builder.next_block(state)).await
+    }
+
+    /// Representative of `account` when opened by this wallet
+    fn open_representative(&self, account: &Address) -> Address {
+        self.representative.clone().unwrap_or_else(|| account.clone())
+    }
+
+    /// Build the next block of `account` from its current state, then sign and publish it
+    async fn publish_block<F>(&self, account: &Address, subtype: BlockSubtype, build: F) -> Result<BlockHash, Error>
+    where
+        F: FnOnce(&AccountState) -> Result<StateBlock, Error>,
+    {
+        let wallet_account = self.account(account)?;
+        let mut cache = wallet_account.state.lock().await;
+        if cache.is_none() {
+            *cache = Some(AccountState::fetch(&self.api, account).await?);
+        }
+        let state = cache.clone().unwrap();
+        let block = build(&state)?;
+        let subtype = if subtype == BlockSubtype::Receive && !state.is_opened() { BlockSubtype::Open } else { subtype };
+        // the block may have been published despite an error, so the state must be fetched again
+        *cache = None;
+        let block = complete(block, &wallet_account.key, &*self.work_generator, self.work_threshold).await?;
+        let hash = self.api.process(&block, ProcessOptions { subtype: Some(subtype), ..Default::default() }).await?;
+        debug!("Published {:?} block {} of {}", subtype, hash, account.0);
+        *cache = Some(AccountState {
+            frontier: hash.clone(),
+            balance: block.balance,
+            representative: Some(block.representative),
+        });
+        Ok(hash)
+    }
+}
+
+/// Add work reaching `work_threshold` and a signature to `block`
+pub(crate) async fn complete(
+    mut block: StateBlock,
+    key: &PrivateKey,
+    work_generator: &dyn WorkGenerator,
+    work_threshold: Difficulty,
+) -> Result<StateBlock, Error> {
+    let root = block.root()?;
+    block.work = Some(work_generator.generate_work(&root, work_threshold).await?);
+    block.sign(key)?;
+    Ok(block)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ProcessError, api::MockNode, units::Banano};
+
+    fn wallet(node: &MockNode) -> Wallet {
+        let mut wallet = Wallet::from_seed(node.api(), Seed([1; 32]))
+            .with_work_threshold(MockNode::low_work_threshold())
+            .with_representative(Address(MockNode::REPRESENTATIVE.into()));
+        wallet.derive(0).unwrap();
+        wallet.derive(1).unwrap();
+        wallet
+    }
+
+    #[tokio::test]
+    async fn sends_receives_and_changes_representative() {
+        let node = MockNode::empty().with_work_threshold(MockNode::low_work_threshold());
+        let wallet = wallet(&node);
+        let addresses: Vec<Address> = wallet.accounts().map(|account| account.account.address.clone()).collect();
+        let (first, second) = (&addresses[0], &addresses[1]);
+        node.add_account(first, Banano::new(10).to_raw().unwrap(), first).unwrap();
+
+        let send = wallet.send(first, second, Banano::new(4).to_raw().unwrap()).await.unwrap();
+        assert_eq!(wallet.balance(first).await.unwrap(), Banano::new(6).to_raw().unwrap());
+        assert!(!wallet.state(second).await.unwrap().is_opened());
+        let error = wallet.receive(first, &send).await.unwrap_err();
+        assert!(matches!(error, Error::NotASend { hash, .. } if hash == send));
+
+        let open = wallet.receive(second, &send).await.unwrap();
+        let state = wallet.state(second).await.unwrap();
+        assert_eq!(state.frontier, open);
+        assert_eq!(state.representative, Some(Address(MockNode::REPRESENTATIVE.into())));
+        assert_eq!(node.account_state(second).unwrap().balance, Banano::new(4).to_raw().unwrap());
+
+        let representative = Address(MockNode::ACCOUNT.into());
+        wallet.change_representative(first, &representative).await.unwrap();
+        assert_eq!(node.account_state(first).unwrap().representative, representative);
+        assert_eq!(wallet.balance(first).await.unwrap(), Banano::new(6).to_raw().unwrap());
+        // account_info is only requested once per account
+        assert_eq!(node.requests_for("account_info").len(), 2);
+        assert_eq!(node.requests_for("process").len(), 3);
+    }
+
+    #[tokio::test]
+    async fn rejects_overspending_and_reloads_stale_state() {
+        let node = MockNode::empty().with_work_threshold(MockNode::low_work_threshold());
+        let wallet = wallet(&node);
+        let first = wallet.accounts().next().unwrap().account.address.clone();
+        node.add_account(&first, Banano::new(1).to_raw().unwrap(), &first).unwrap();
+
+        let error = wallet.send(&first, &first, Banano::new(2).to_raw().unwrap()).await.unwrap_err();
+        assert!(matches!(error, Error::InsufficientBalance { .. }));
+        assert!(matches!(wallet.change_representative(&Address(MockNode::ACCOUNT.into()), &first).await, Err(Error::AccountNotInWallet { .. })));
+
+        // blocks published elsewhere make the cached frontier stale
+        node.add_receivable(&first, &Address(MockNode::ACCOUNT.into()), Raw::new(1u128)).unwrap();
+        wallet.state(&first).await.unwrap();
+        let mut account = node.account_state(&first).unwrap();
+        account.frontier = node.receivable_blocks(&first)[0].clone();
+        node.set_account(&first, account);
+        let error = wallet.send(&first, &first, Raw::new(1u128)).await.unwrap_err();
+        assert!(matches!(error, Error::ProcessError(ProcessError::Fork)));
+        wallet.send(&first, &first, Raw::new(1u128)).await.unwrap();
+    }
+}
@@ -0,0 +1,426 @@
+use crate::{Address, BananoApi, Error, ProcessError, Raw, api::{BlockContents, ReceivableOptions, RetryPolicy}, payments::{Payment, PaymentWatcher}, types::{BlockHash, BlockSubtype, Link, PrivateKey, StateBlock}};
+use super::{AccountState, Wallet, builder::BlockBuilder};
+use futures::{future::{self, Either}, stream, Stream, StreamExt};
+use log::{debug, warn};
+use std::{pin::Pin, sync::{Arc, Mutex}, time::Duration};
+use tokio::time::{sleep_until, Instant};
+
+type Payments = Pin<Box<dyn Stream<Item = Result<Payment, Error>> + Send>>;
+
+/// Funds received by a [Wallet]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Received {
+    pub account: Address,
+    /// Hash of the send block which was received
+    pub source: BlockHash,
+    pub amount: Raw,
+    /// Hash of the receive block
+    pub hash: BlockHash,
+}
+
+/// Builder for a receive block, pocketing funds sent to the account of a [PrivateKey]
+///
+/// The receive block opens the account when it has no block yet. The amount is fetched with
+/// `block_info` and the state of the account with `account_info`, unless already known.
+///
+/// # Example:
+/// ```no_run
+/// use banano_rs::{Address, BananoApi, types::{BlockHash, PrivateKey, Seed}, wallet::ReceiveBuilder};
+/// use std::str::FromStr;
+///
+/// # async fn run() -> banano_rs::Result<()> {
+/// let banano = BananoApi::new("https://kaliumapi.appditto.com/api".into());
+/// let key = PrivateKey::from_seed(Seed::from("1234567890123456789012345678901234567890123456789012345678901234")?, 0);
+/// let send = BlockHash::from_str("40DB7EC1F71F7B3B66982007F20E687148BDB875E533121259C0BF69AEFE88D3")?;
+/// let hash = ReceiveBuilder::new(banano, key, send)
+///     .open_representative(Address("ban_1fomoz167m7o38gw4rzt7hz67oq6itejpt4yocrfywujbpatd711cjew8gjj".into()))
+///     .publish()
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct ReceiveBuilder {
+    base: BlockBuilder,
+    source: BlockHash,
+    amount: Option<Raw>,
+    open_representative: Option<Address>,
+}
+
+impl ReceiveBuilder {
+    /// Receive the send block `source` with the account of `key`
+    pub fn new(api: BananoApi, key: PrivateKey, source: BlockHash) -> Self {
+        ReceiveBuilder {
+            base: BlockBuilder::new(api, key),
+            source,
+            amount: None,
+            open_representative: None,
+        }
+    }
+
+    /// Amount of the send block, when already known
+    pub fn amount(mut self, amount: Raw) -> Self {
+        self.amount = Some(amount);
+        self
+    }
+
+    /// Representative of the account if this block opens it, defaulting to the account itself
+    pub fn open_representative(mut self, representative: Address) -> Self {
+        self.open_representative = Some(representative);
+        self
+    }
+
+    block_builder_setters!();
+
+    /// Amount of the send block, fetched with `block_info` unless set
+    ///
+    /// Fails with [Error::NotASend] when the fetched block is not a send to the account.
+    pub(crate) async fn fetch_amount(&mut self) -> Result<Raw, Error> {
+        if self.amount.is_none() {
+            let info = self.base.api.block_info(&self.source).await?;
+            let is_send = match &info.contents {
+                BlockContents::State(block) => info.subtype == Some(BlockSubtype::Send) && block.link.to_address() == self.base.account,
+                BlockContents::Legacy(block) => block["type"] == "send" && block["destination"] == self.base.account.0.as_str(),
+            };
+            let not_a_send = || Error::NotASend { hash: self.source.clone(), address: self.base.account.clone() };
+            if !is_send {
+                return Err(not_a_send());
+            }
+            self.amount = Some(info.amount.ok_or_else(not_a_send)?);
+        }
+        Ok(self.amount.clone().unwrap())
+    }
+
+    /// Unsigned receive block following `state`, once the amount is known
+    pub(crate) fn next_block(&self, state: &AccountState) -> Result<StateBlock, Error> {
+        let amount = self.amount.as_ref().expect("amount of the received block is known");
+        let balance = state.balance.checked_add(amount).ok_or_else(|| Error::BalanceOverflow {
+            address: self.base.account.clone(),
+            amount: amount.clone(),
+        })?;
+        let representative = state
+            .representative
+            .clone()
+            .or_else(|| self.open_representative.clone())
+            .unwrap_or_else(|| self.base.account.clone());
+        Ok(state.next_block(&self.base.account, balance, representative, Link::from(self.source.clone())))
+    }
+
+    /// Signed receive block, with its work, ready to be published
+    pub async fn build(mut self) -> Result<StateBlock, Error> {
+        self.fetch_amount().await?;
+        self.base.build(|state| self.next_block(state)).await
+    }
+
+    /// Build the receive block and publish it, returning its hash
+    pub async fn publish(mut self) -> Result<BlockHash, Error> {
+        self.fetch_amount().await?;
+        let block = self.base.build(|state| self.next_block(state)).await?;
+        self.base.publish(&block, BlockSubtype::Receive).await
+    }
+}
+
+/// Outcome for one block of [Wallet::receive_all]
+#[derive(Debug)]
+pub struct ReceiveResult {
+    /// Hash of the send block
+    pub source: BlockHash,
+    pub result: Result<Received, Error>,
+}
+
+impl Wallet {
+    /// Receive every block sent to `account` with an amount of at least `threshold`, largest first.
+    ///
+    /// Returns the outcome for each block: a block which cannot be received does not prevent
+    /// receiving the next ones.
+    pub async fn receive_all(&self, account: &Address, threshold: Option<Raw>) -> Result<Vec<ReceiveResult>, Error> {
+        let options = ReceivableOptions {
+            threshold,
+            sorting: true,
+            ..Default::default()
+        };
+        let blocks = self.api.receivable(account, options).await?;
+        let mut results = Vec::with_capacity(blocks.len());
+        for (source, receivable) in blocks {
+            let result = self.receive_amount(account, &source, receivable.amount.clone()).await.map(|hash| Received {
+                account: account.clone(),
+                source: source.clone(),
+                amount: receivable.amount,
+                hash,
+            });
+            if let Err(error) = &result {
+                warn!("Cannot receive {} with {}: {}", source, account.0, error);
+            }
+            results.push(ReceiveResult { source, result });
+        }
+        Ok(results)
+    }
+}
+
+/// Receives the payments found by a [PaymentWatcher] with a [Wallet]
+///
+/// Only confirmed payments are received. Receiving a payment is tried again with the delays of a
+/// [RetryPolicy], by default up to 5 times over about a minute; a payment received in the
+/// meantime by someone else is dropped. A payment still failing after that is handed back to the
+/// watcher, which yields it again at its next poll if it is still receivable.
+///
+/// # Example:
+/// ```no_run
+/// use banano_rs::{BananoApi, payments::PaymentWatcher, types::Seed, wallet::{AutoReceiver, Wallet}};
+/// use futures::StreamExt;
+/// use std::sync::Arc;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), banano_rs::Error> {
+///     let banano = BananoApi::new("https://kaliumapi.appditto.com/api".into());
+///     let seed = Seed::from("1234567890123456789012345678901234567890123456789012345678901234")?;
+///     let mut wallet = Wallet::from_seed(banano.clone(), seed);
+///     for index in 0..10 {
+///         wallet.derive(index)?;
+///     }
+///     let watcher = PaymentWatcher::new(banano, wallet.addresses());
+///     let mut received = Box::pin(AutoReceiver::new(Arc::new(wallet), watcher).received());
+///     while let Some(received) = received.next().await {
+///         match received {
+///             Ok(received) => println!("{} received {}", received.account.0, received.amount),
+///             Err(error) => eprintln!("{}", error),
+///         }
+///     }
+///     Ok(())
+/// }
+/// ```
+pub struct AutoReceiver {
+    wallet: Arc<Wallet>,
+    watcher: PaymentWatcher,
+    retry_policy: RetryPolicy,
+}
+
+impl AutoReceiver {
+    /// Receive the payments of `watcher`, which should only watch accounts of `wallet`
+    pub fn new(wallet: Arc<Wallet>, watcher: PaymentWatcher) -> Self {
+        AutoReceiver {
+            wallet,
+            watcher,
+            retry_policy: RetryPolicy {
+                max_retries: 5,
+                initial_backoff: Duration::from_secs(2),
+                max_backoff: Duration::from_secs(30),
+                jitter: true,
+            },
+        }
+    }
+
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Funds received, along with failures to poll the node or to receive a payment. Ends when
+    /// the payments of the watcher end.
+    pub fn received(self) -> impl Stream<Item = Result<Received, Error>> {
+        let receiver = Receiver {
+            wallet: self.wallet,
+            forgotten: self.watcher.forgotten(),
+            payments: Box::pin(self.watcher.payments()),
+            retry_policy: self.retry_policy,
+            retries: Vec::new(),
+        };
+        stream::unfold(receiver, |mut receiver| async move {
+            receiver.next().await.map(|received| (received, receiver))
+        })
+    }
+}
+
+struct Retry {
+    payment: Payment,
+    attempt: u32,
+    due: Instant,
+}
+
+struct Receiver {
+    wallet: Arc<Wallet>,
+    /// Payments given up on, for the watcher to yield again
+    forgotten: Arc<Mutex<Vec<BlockHash>>>,
+    payments: Payments,
+    retry_policy: RetryPolicy,
+    retries: Vec<Retry>,
+}
+
+impl Receiver {
+    async fn next(&mut self) -> Option<Result<Received, Error>> {
+        loop {
+            let retry = self.retries.iter().enumerate().min_by_key(|(_, retry)| retry.due).map(|(position, retry)| (position, retry.due));
+            let payment = match retry {
+                Some((position, due)) => {
+                    match future::select(self.payments.next(), Box::pin(sleep_until(due))).await {
+                        Either::Left((payment, _)) => payment,
+                        Either::Right(_) => {
+                            let retry = self.retries.swap_remove(position);
+                            return Some(self.receive(retry.payment, retry.attempt + 1).await);
+                        }
+                    }
+                }
+                None => self.payments.next().await,
+            };
+            match payment? {
+                Ok(payment) if payment.confirmed => return Some(self.receive(payment, 0).await),
+                Ok(_) => {}
+                Err(error) => return Some(Err(error)),
+            }
+        }
+    }
+
+    async fn receive(&mut self, payment: Payment, attempt: u32) -> Result<Received, Error> {
+        match self.wallet.receive_amount(&payment.account, &payment.hash, payment.amount.clone()).await {
+            Ok(hash) => Ok(Received {
+                account: payment.account,
+                source: payment.hash,
+                amount: payment.amount,
+                hash,
+            }),
+            Err(error) => {
+                let received = matches!(error, Error::ProcessError(ProcessError::Unreceivable) | Error::ProcessError(ProcessError::Old));
+                if received || matches!(error, Error::AccountNotInWallet { .. } | Error::BalanceOverflow { .. }) {
+                    debug!("Not receiving {}: {}", payment.hash, error);
+                } else if attempt < self.retry_policy.max_retries {
+                    let backoff = self.retry_policy.backoff(attempt);
+                    warn!("Receiving {} again in {:?} after error: {}", payment.hash, backoff, error);
+                    self.retries.push(Retry {
+                        payment,
+                        attempt,
+                        due: Instant::now() + backoff,
+                    });
+                } else {
+                    warn!("Giving up on receiving {} until the next poll: {}", payment.hash, error);
+                    self.forgotten.lock().unwrap().push(payment.hash);
+                }
+                Err(error)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{api::MockNode, payments::WatcherOptions, types::{Account, Seed}, units::Banano};
+    use crate::wallet::SendBuilder;
+
+    #[tokio::test]
+    async fn opens_accounts_with_the_chosen_representative() {
+        let node = MockNode::new().with_work_threshold(MockNode::low_work_threshold());
+        let key = PrivateKey::from_seed(Seed([3; 32]), 0);
+        let account = Account::from(key.clone()).address;
+        let source = node.add_receivable(&account, &Address(MockNode::ACCOUNT.into()), Banano::new(1).to_raw().unwrap()).unwrap();
+
+        let hash = ReceiveBuilder::new(node.api(), key, source)
+            .open_representative(Address(MockNode::REPRESENTATIVE.into()))
+            .work_threshold(MockNode::low_work_threshold())
+            .publish()
+            .await
+            .unwrap();
+        let state = node.account_state(&account).unwrap();
+        assert_eq!(state.open_block, hash);
+        assert_eq!(state.balance, Banano::new(1).to_raw().unwrap());
+        assert_eq!(state.representative, Address(MockNode::REPRESENTATIVE.into()));
+    }
+
+    #[tokio::test]
+    async fn receives_all_blocks_above_threshold() {
+        let node = MockNode::new().with_work_threshold(MockNode::low_work_threshold());
+        let mut wallet = Wallet::from_seed(node.api(), Seed([3; 32])).with_work_threshold(MockNode::low_work_threshold());
+        let account = wallet.derive(0).unwrap().address.clone();
+        let sender = Address(MockNode::ACCOUNT.into());
+        node.add_receivable(&account, &sender, Banano::new(1).to_raw().unwrap()).unwrap();
+        node.add_receivable(&account, &sender, Banano::new(2).to_raw().unwrap()).unwrap();
+        node.add_receivable(&account, &sender, Raw::new(1u128)).unwrap();
+
+        let received: Vec<Received> = wallet
+            .receive_all(&account, Some(Banano::new(1).to_raw().unwrap()))
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|outcome| outcome.result.unwrap())
+            .collect();
+        let amounts: Vec<Raw> = received.iter().map(|received| received.amount.clone()).collect();
+        assert_eq!(amounts, vec![Banano::new(2).to_raw().unwrap(), Banano::new(1).to_raw().unwrap()]);
+        assert_eq!(wallet.balance(&account).await.unwrap(), Banano::new(3).to_raw().unwrap());
+        assert_eq!(node.receivable_blocks(&account).len(), 1);
+        assert_eq!(node.account_state(&account).unwrap().frontier, received[1].hash);
+
+        // failures are reported for each block
+        let full = wallet.derive(1).unwrap().address.clone();
+        node.add_account(&full, Raw::max(), &full).unwrap();
+        node.add_receivable(&full, &sender, Raw::new(1u128)).unwrap();
+        node.add_receivable(&full, &sender, Raw::new(2u128)).unwrap();
+        let results = wallet.receive_all(&full, None).await.unwrap();
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|outcome| matches!(outcome.result, Err(Error::BalanceOverflow { .. }))));
+    }
+
+    #[tokio::test]
+    async fn receives_payments_and_retries_failures() {
+        let node = MockNode::new().with_work_threshold(MockNode::low_work_threshold());
+        let mut wallet = Wallet::from_seed(node.api(), Seed([4; 32])).with_work_threshold(MockNode::low_work_threshold());
+        let account = wallet.derive(0).unwrap().address.clone();
+        let wallet = Arc::new(wallet);
+        let sender = Address(MockNode::ACCOUNT.into());
+        node.add_account(&account, Banano::new(5).to_raw().unwrap(), &sender).unwrap();
+        wallet.state(&account).await.unwrap();
+
+        // a block published outside of the wallet makes its cached frontier stale
+        SendBuilder::new(node.api(), PrivateKey::from_seed(Seed([4; 32]), 0), sender.clone(), Raw::new(1u128))
+            .work_threshold(MockNode::low_work_threshold())
+            .publish()
+            .await
+            .unwrap();
+        let first = node.add_receivable(&account, &sender, Banano::new(1).to_raw().unwrap()).unwrap();
+
+        let watcher = PaymentWatcher::new(node.api(), wallet.addresses()).with_options(WatcherOptions {
+            poll_interval: Duration::from_millis(50),
+            ..Default::default()
+        });
+        let retry_policy = RetryPolicy {
+            initial_backoff: Duration::from_millis(10),
+            ..Default::default()
+        };
+        let mut received = Box::pin(AutoReceiver::new(wallet.clone(), watcher).with_retry_policy(retry_policy).received());
+        assert!(matches!(received.next().await, Some(Err(Error::ProcessError(ProcessError::Fork)))));
+        assert_eq!(received.next().await.unwrap().unwrap().source, first);
+
+        let second = node.add_receivable(&account, &sender, Banano::new(2).to_raw().unwrap()).unwrap();
+        assert_eq!(received.next().await.unwrap().unwrap().source, second);
+        let balance = Banano::new(8).to_raw().unwrap().checked_sub(&Raw::new(1u128)).unwrap();
+        assert_eq!(node.account_state(&account).unwrap().balance, balance);
+        assert_eq!(wallet.balance(&account).await.unwrap(), balance);
+    }
+
+    #[tokio::test]
+    async fn hands_exhausted_payments_back_to_the_watcher() {
+        let node = MockNode::new().with_work_threshold(MockNode::low_work_threshold());
+        let mut wallet = Wallet::from_seed(node.api(), Seed([6; 32])).with_work_threshold(MockNode::low_work_threshold());
+        let account = wallet.derive(0).unwrap().address.clone();
+        let wallet = Arc::new(wallet);
+        let sender = Address(MockNode::ACCOUNT.into());
+        node.add_account(&account, Banano::new(5).to_raw().unwrap(), &sender).unwrap();
+        wallet.state(&account).await.unwrap();
+        SendBuilder::new(node.api(), PrivateKey::from_seed(Seed([6; 32]), 0), sender.clone(), Raw::new(1u128))
+            .work_threshold(MockNode::low_work_threshold())
+            .publish()
+            .await
+            .unwrap();
+        let payment = node.add_receivable(&account, &sender, Banano::new(1).to_raw().unwrap()).unwrap();
+
+        let watcher = PaymentWatcher::new(node.api(), wallet.addresses()).with_options(WatcherOptions {
+            poll_interval: Duration::from_millis(50),
+            ..Default::default()
+        });
+        let retry_policy = RetryPolicy {
+            max_retries: 0,
+            ..Default::default()
+        };
+        let mut received = Box::pin(AutoReceiver::new(wallet, watcher).with_retry_policy(retry_policy).received());
+        assert!(matches!(received.next().await, Some(Err(Error::ProcessError(ProcessError::Fork)))));
+        // found again by the next poll, without any retry left
+        assert_eq!(received.next().await.unwrap().unwrap().source, payment);
+    }
+}
@@ -0,0 +1,113 @@
+use crate::{Address, BananoApi, Error, Raw, types::{BlockHash, BlockSubtype, Link, PrivateKey, StateBlock}};
+use super::{AccountState, builder::BlockBuilder};
+
+/// Builder for a send block, spending funds of the account of a [PrivateKey]
+///
+/// The balance and frontier of the account are fetched with `account_info` unless already
+/// known, see [state](SendBuilder::state). Work is generated on the CPU unless another
+/// [WorkGenerator] is set, like the node itself for remote work.
+///
+/// # Example:
+/// ```no_run
+/// use banano_rs::{Address, BananoApi, types::{PrivateKey, Seed}, units::Banano, wallet::SendBuilder};
+///
+/// # async fn run() -> banano_rs::Result<()> {
+/// let banano = BananoApi::new("https://kaliumapi.appditto.com/api".into());
+/// let key = PrivateKey::from_seed(Seed::from("1234567890123456789012345678901234567890123456789012345678901234")?, 0);
+/// let destination = Address("ban_1hgtqu7cmgxb66ta4gxt7coimqcxp86nzi5b7u14ip9zzpqr16a3dbqdja1f".into());
+/// let hash = SendBuilder::new(banano.clone(), key, destination, Banano::new(1).to_raw()?)
+///     .work_generator(banano)
+///     .publish()
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct SendBuilder {
+    base: BlockBuilder,
+    destination: Address,
+    amount: Raw,
+    representative: Option<Address>,
+}
+
+impl SendBuilder {
+    /// Send `amount` from the account of `key` to `destination`
+    pub fn new(api: BananoApi, key: PrivateKey, destination: Address, amount: Raw) -> Self {
+        SendBuilder {
+            base: BlockBuilder::new(api, key),
+            destination,
+            amount,
+            representative: None,
+        }
+    }
+
+    /// Also delegate the account to `representative`, instead of keeping its current one
+    pub fn representative(mut self, representative: Address) -> Self {
+        self.representative = Some(representative);
+        self
+    }
+
+    block_builder_setters!();
+
+    /// Unsigned send block following `state`
+    ///
+    /// Fails with [Error::InsufficientBalance] when the account cannot afford the amount.
+    pub(crate) fn next_block(&self, state: &AccountState) -> Result<StateBlock, Error> {
+        let balance = state.balance.checked_sub(&self.amount).ok_or_else(|| Error::InsufficientBalance {
+            balance: state.balance.clone(),
+            amount: self.amount.clone(),
+        })?;
+        let representative = self
+            .representative
+            .clone()
+            .or_else(|| state.representative.clone())
+            .ok_or_else(|| Error::AccountNotOpened { address: self.base.account.clone() })?;
+        Ok(state.next_block(&self.base.account, balance, representative, Link::from(self.destination.to_public_key()?)))
+    }
+
+    /// Signed send block, with its work, ready to be published
+    pub async fn build(self) -> Result<StateBlock, Error> {
+        self.base.build(|state| self.next_block(state)).await
+    }
+
+    /// Build the send block and publish it, returning its hash
+    pub async fn publish(self) -> Result<BlockHash, Error> {
+        let block = self.base.build(|state| self.next_block(state)).await?;
+        self.base.publish(&block, BlockSubtype::Send).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{api::MockNode, types::{Account, Seed}, units::Banano};
+
+    #[tokio::test]
+    async fn publishes_send_blocks() {
+        let threshold = MockNode::low_work_threshold();
+        let node = MockNode::new().with_work_threshold(threshold);
+        let key = PrivateKey::from_seed(Seed([2; 32]), 0);
+        let account = Account::from(key.clone()).address;
+        node.add_account(&account, Banano::new(5).to_raw().unwrap(), &Address(MockNode::REPRESENTATIVE.into())).unwrap();
+        let destination = Address(MockNode::ACCOUNT.into());
+
+        let error = SendBuilder::new(node.api(), key.clone(), destination.clone(), Banano::new(6).to_raw().unwrap())
+            .publish()
+            .await
+            .unwrap_err();
+        assert!(matches!(error, Error::InsufficientBalance { balance, .. } if balance == Banano::new(5).to_raw().unwrap()));
+
+        let hash = SendBuilder::new(node.api(), key, destination.clone(), Banano::new(2).to_raw().unwrap())
+            .work_generator(node.api())
+            .work_threshold(threshold)
+            .representative(destination.clone())
+            .publish()
+            .await
+            .unwrap();
+        let block = node.block(&hash).unwrap();
+        assert_eq!(block.balance, Banano::new(3).to_raw().unwrap());
+        assert_eq!(block.link.to_address(), destination);
+        assert_eq!(block.representative, destination);
+        assert!(node.receivable_blocks(&destination).contains(&hash));
+        assert_eq!(node.requests_for("work_generate").len(), 1);
+    }
+}
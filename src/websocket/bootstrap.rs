@@ -0,0 +1,50 @@
+use serde::Deserialize;
+use serde_with::{serde_as, DisplayFromStr};
+
+/// Bootstrap attempt of the node, from the `bootstrap` topic
+///
+/// [Nano documentation](https://docs.nano.org/integration-guides/websockets/#bootstrap)
+#[serde_as]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum Bootstrap {
+    Started {
+        id: String,
+        /// Like `legacy`, `lazy` or `wallet_lazy`
+        mode: String,
+    },
+    Exited {
+        id: String,
+        mode: String,
+        #[serde_as(as = "DisplayFromStr")]
+        total_blocks: u64,
+        /// Duration in seconds
+        #[serde_as(as = "DisplayFromStr")]
+        duration: u64,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn deserialize_bootstrap() {
+        let started: Bootstrap = serde_json::from_value(json!({
+            "reason": "started",
+            "id": "C6A3E4D6A6E3F0A9",
+            "mode": "legacy"
+        })).unwrap();
+        assert!(matches!(started, Bootstrap::Started { mode, .. } if mode == "legacy"));
+
+        let exited: Bootstrap = serde_json::from_value(json!({
+            "reason": "exited",
+            "id": "C6A3E4D6A6E3F0A9",
+            "mode": "legacy",
+            "total_blocks": "1000000",
+            "duration": "120"
+        })).unwrap();
+        assert!(matches!(exited, Bootstrap::Exited { total_blocks: 1000000, duration: 120, .. }));
+    }
+}
@@ -0,0 +1,196 @@
+use crate::{Address, Raw, types::{BlockHash, BlockSubtype, StateBlock}};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use serde_with::{serde_as, DisplayFromStr};
+
+/// How an election was confirmed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfirmationType {
+    /// Confirmed by votes on the block itself
+    ActiveQuorum,
+    /// Confirmed as a dependency of a later block
+    ActiveConfirmationHeight,
+    /// Confirmed without an election on this node
+    Inactive,
+    /// Missed while disconnected and recovered from the account history by a
+    /// [WebSocketSession](super::WebSocketSession)
+    #[serde(skip)]
+    Backfill,
+}
+
+/// Options of the `confirmation` topic
+///
+/// Only confirmations involving `accounts` are sent, unless it is empty.
+///
+/// [Nano documentation](https://docs.nano.org/integration-guides/websockets/#confirmations)
+#[derive(Debug, Clone)]
+pub struct ConfirmationOptions {
+    pub accounts: Vec<Address>,
+    /// Also watch the accounts of the node wallets
+    pub all_local_accounts: bool,
+    /// Send the contents of the block, which is needed for its subtype. Enabled by default.
+    pub include_block: bool,
+    pub include_election_info: bool,
+    /// Only send confirmations of this type, all of them by default
+    pub confirmation_type: Option<ConfirmationFilter>,
+}
+
+impl Default for ConfirmationOptions {
+    fn default() -> Self {
+        ConfirmationOptions {
+            accounts: Vec::new(),
+            all_local_accounts: false,
+            include_block: true,
+            include_election_info: false,
+            confirmation_type: None,
+        }
+    }
+}
+
+/// Kinds of confirmations sent on the `confirmation` topic
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfirmationFilter {
+    All,
+    /// [ActiveQuorum](ConfirmationType::ActiveQuorum) and
+    /// [ActiveConfirmationHeight](ConfirmationType::ActiveConfirmationHeight)
+    Active,
+    ActiveQuorum,
+    ActiveConfirmationHeight,
+    Inactive,
+}
+
+impl ConfirmationOptions {
+    /// Confirmations involving `accounts`
+    pub fn accounts(accounts: Vec<Address>) -> Self {
+        ConfirmationOptions {
+            accounts,
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn to_json(&self) -> Value {
+        let mut options = json!({
+            "include_block": self.include_block.to_string(),
+            "include_election_info": self.include_election_info.to_string(),
+        });
+        if !self.accounts.is_empty() {
+            options["accounts"] = json!(self.accounts);
+        }
+        if self.all_local_accounts {
+            options["all_local_accounts"] = json!("true");
+        }
+        if let Some(confirmation_type) = &self.confirmation_type {
+            options["confirmation_type"] = json!(confirmation_type);
+        }
+        options
+    }
+}
+
+/// Confirmed block, from the `confirmation` topic
+#[derive(Debug, Clone, Deserialize)]
+pub struct Confirmation {
+    /// Account owning the block
+    pub account: Address,
+    /// Amount sent or received, zero for a change block
+    pub amount: Raw,
+    pub hash: BlockHash,
+    pub confirmation_type: ConfirmationType,
+    /// Only available with [include_block](ConfirmationOptions::include_block)
+    #[serde(rename = "block")]
+    pub contents: Option<BlockWithSubtype>,
+    /// Only available with [include_election_info](ConfirmationOptions::include_election_info)
+    pub election_info: Option<ElectionInfo>,
+}
+
+impl Confirmation {
+    /// Subtype of the block, when its contents were included
+    pub fn subtype(&self) -> Option<BlockSubtype> {
+        self.contents.as_ref().map(|contents| contents.subtype)
+    }
+
+    /// Account credited by a send block, when its contents were included
+    pub fn destination(&self) -> Option<Address> {
+        match &self.contents {
+            Some(contents) if contents.subtype == BlockSubtype::Send => Some(contents.block.link.to_address()),
+            _ => None,
+        }
+    }
+}
+
+/// Contents of a block, from the `confirmation` and `new_unconfirmed_block` topics
+#[derive(Debug, Clone, Deserialize)]
+pub struct BlockWithSubtype {
+    #[serde(flatten)]
+    pub block: StateBlock,
+    pub subtype: BlockSubtype,
+}
+
+/// Election started or stopped without confirmation, from the `started_election` and
+/// `stopped_election` topics
+#[derive(Debug, Clone, Deserialize)]
+pub struct Election {
+    /// Hash of the block the election was about
+    pub hash: BlockHash,
+}
+
+/// Election which confirmed a block
+#[serde_as]
+#[derive(Debug, Clone, Deserialize)]
+pub struct ElectionInfo {
+    /// Duration of the election in milliseconds
+    #[serde_as(as = "DisplayFromStr")]
+    pub duration: u64,
+    /// Time of the confirmation, in milliseconds since the UNIX epoch
+    #[serde_as(as = "DisplayFromStr")]
+    pub time: u64,
+    pub tally: Raw,
+    pub final_tally: Option<Raw>,
+    #[serde_as(as = "DisplayFromStr")]
+    pub request_count: u32,
+    #[serde_as(as = "DisplayFromStr")]
+    pub blocks: u32,
+    #[serde_as(as = "DisplayFromStr")]
+    pub voters: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_confirmation() {
+        let confirmation: Confirmation = serde_json::from_value(json!({
+            "account": "ban_1hgtqu7cmgxb66ta4gxt7coimqcxp86nzi5b7u14ip9zzpqr16a3dbqdja1f",
+            "amount": "100000000000000000000000000000",
+            "hash": "40DB7EC1F71F7B3B66982007F20E687148BDB875E533121259C0BF69AEFE88D3",
+            "confirmation_type": "active_quorum",
+            "block": {
+                "type": "state",
+                "account": "ban_1hgtqu7cmgxb66ta4gxt7coimqcxp86nzi5b7u14ip9zzpqr16a3dbqdja1f",
+                "previous": "2F20C3F42DD5F0A7B3C6D8E6E90C1D8E1F8D2D31A0D6A86E8C6B4F5D0B2E3F1A",
+                "representative": "ban_1fomoz167m7o38gw4rzt7hz67oq6itejpt4yocrfywujbpatd711cjew8gjj",
+                "balance": "9800000000000000000000000000000",
+                "link": "5D1AA8E4D4A4BE7C9E5A4A3CCA7DDA1E1B1FA5B2D6F1B9B2E1F0E2C9B1A8F9D3",
+                "link_as_account": "ban_1qaao5kfbb7yhkh7nkjwsbyxn9iu5ykd7oqjp8pl5w94q8ftm6ymybanfe3y",
+                "signature": "5B11B17DB9C8FE0CC58CAC6A6EECEF9CB122DA8A81C6D3DB1B5EE3AB065AA8F8CB1D6765C8EB91B58530C5FF5987AD95E6D34BB57F44257E20795EE412E61600",
+                "work": "2BF29EF00786A6BC",
+                "subtype": "send"
+            },
+            "election_info": {
+                "duration": "546",
+                "time": "1624270400000",
+                "tally": "42000000000000000000000000000000000",
+                "request_count": "1",
+                "blocks": "1",
+                "voters": "52"
+            }
+        })).unwrap();
+        assert_eq!(confirmation.confirmation_type, ConfirmationType::ActiveQuorum);
+        assert_eq!(confirmation.amount, Raw::new(100000000000000000000000000000u128));
+        assert_eq!(confirmation.subtype(), Some(BlockSubtype::Send));
+        assert_eq!(confirmation.destination().unwrap().to_public_key().unwrap().0[0], 0x5D);
+        assert_eq!(confirmation.election_info.unwrap().voters, 52);
+    }
+}
@@ -0,0 +1,390 @@
+//! Client for the websocket of a node, available with the `websocket` feature
+//!
+//! Nodes push notifications, like confirmed blocks, to websocket clients subscribed to their topics.
+//!
+//! # Example:
+//! ```no_run
+//! use banano_rs::{Address, websocket::{ConfirmationOptions, Subscription, WebSocketClient}};
+//! use futures::StreamExt;
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), banano_rs::Error> {
+//!     let client = WebSocketClient::connect("ws://localhost:7074").await?;
+//!     let address = Address("ban_1hgtqu7cmgxb66ta4gxt7coimqcxp86nzi5b7u14ip9zzpqr16a3dbqdja1f".into());
+//!     client.subscribe(Subscription::Confirmation(ConfirmationOptions::accounts(vec![address])))?;
+//!
+//!     let mut confirmations = Box::pin(client.confirmations());
+//!     while let Some(confirmation) = confirmations.next().await {
+//!         let confirmation = confirmation?;
+//!         println!("{} {:?} {}", confirmation.account.0, confirmation.subtype(), confirmation.amount);
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use crate::{Address, Error, api::{ActiveDifficulty, Telemetry}};
+pub use self::bootstrap::*;
+pub use self::confirmation::*;
+pub use self::session::*;
+pub use self::vote::*;
+pub use self::work::*;
+use futures::{channel::mpsc, future::{self, Either}, stream, SinkExt, Stream, StreamExt};
+use log::debug;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::net::TcpStream;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream, connect_async, tungstenite};
+
+mod bootstrap;
+mod confirmation;
+mod session;
+mod vote;
+mod work;
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// Topics of the websocket of a node
+///
+/// [Nano documentation](https://docs.nano.org/integration-guides/websockets/#available-topics)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Topic {
+    Confirmation,
+    Vote,
+    StoppedElection,
+    StartedElection,
+    ActiveDifficulty,
+    Work,
+    Telemetry,
+    NewUnconfirmedBlock,
+    Bootstrap,
+}
+
+/// Subscription to a topic, with its options
+#[derive(Debug, Clone)]
+pub enum Subscription {
+    Confirmation(ConfirmationOptions),
+    Vote(VoteOptions),
+    /// Elections stopped without confirmation, after a timeout
+    StoppedElection,
+    StartedElection,
+    /// Changes of the difficulty required by the network
+    ActiveDifficulty,
+    /// Work generated by the node or its work peers
+    Work,
+    /// Telemetry received from peers
+    Telemetry,
+    /// Blocks processed by the node, before their confirmation
+    NewUnconfirmedBlock,
+    Bootstrap,
+}
+
+impl Subscription {
+    pub fn topic(&self) -> Topic {
+        match self {
+            Subscription::Confirmation(_) => Topic::Confirmation,
+            Subscription::Vote(_) => Topic::Vote,
+            Subscription::StoppedElection => Topic::StoppedElection,
+            Subscription::StartedElection => Topic::StartedElection,
+            Subscription::ActiveDifficulty => Topic::ActiveDifficulty,
+            Subscription::Work => Topic::Work,
+            Subscription::Telemetry => Topic::Telemetry,
+            Subscription::NewUnconfirmedBlock => Topic::NewUnconfirmedBlock,
+            Subscription::Bootstrap => Topic::Bootstrap,
+        }
+    }
+
+    fn options(&self) -> Option<Value> {
+        match self {
+            Subscription::Confirmation(options) => Some(options.to_json()),
+            Subscription::Vote(options) => Some(options.to_json()),
+            _ => None,
+        }
+    }
+}
+
+/// Notification pushed by the node
+#[derive(Debug, Clone)]
+pub enum Message {
+    Confirmation(Confirmation),
+    Vote(Vote),
+    StoppedElection(Election),
+    StartedElection(Election),
+    ActiveDifficulty(ActiveDifficulty),
+    Work(WorkGeneration),
+    /// Telemetry of a single peer, with its address and port
+    Telemetry(Telemetry),
+    NewUnconfirmedBlock(BlockWithSubtype),
+    Bootstrap(Bootstrap),
+}
+
+#[derive(Deserialize)]
+struct Envelope {
+    topic: Option<Topic>,
+    #[serde(default)]
+    message: Value,
+}
+
+impl Message {
+    /// Parse a text frame, `None` for frames which are not notifications, like acks
+    fn parse(text: &str) -> Result<Option<Message>, Error> {
+        let envelope: Envelope = match serde_json::from_str(text) {
+            Ok(envelope) => envelope,
+            Err(_) => {
+                debug!("Ignoring websocket frame {}", text);
+                return Ok(None);
+            }
+        };
+        let message = envelope.message;
+        let message = match envelope.topic {
+            Some(Topic::Confirmation) => Message::Confirmation(serde_json::from_value(message)?),
+            Some(Topic::Vote) => Message::Vote(serde_json::from_value(message)?),
+            Some(Topic::StoppedElection) => Message::StoppedElection(serde_json::from_value(message)?),
+            Some(Topic::StartedElection) => Message::StartedElection(serde_json::from_value(message)?),
+            Some(Topic::ActiveDifficulty) => Message::ActiveDifficulty(serde_json::from_value(message)?),
+            Some(Topic::Work) => Message::Work(serde_json::from_value(message)?),
+            Some(Topic::Telemetry) => Message::Telemetry(serde_json::from_value(message)?),
+            Some(Topic::NewUnconfirmedBlock) => Message::NewUnconfirmedBlock(serde_json::from_value(message)?),
+            Some(Topic::Bootstrap) => Message::Bootstrap(serde_json::from_value(message)?),
+            None => return Ok(None),
+        };
+        Ok(Some(message))
+    }
+}
+
+/// Change of subscriptions requested through a [WebSocketHandle]
+#[derive(Debug, Clone)]
+enum Command {
+    Subscribe(Subscription),
+    Unsubscribe(Topic),
+    UpdateAccounts { add: Vec<Address>, remove: Vec<Address> },
+}
+
+impl Command {
+    fn to_json(&self) -> Value {
+        match self {
+            Command::Subscribe(subscription) => {
+                let mut command = json!({
+                    "action": "subscribe",
+                    "topic": subscription.topic(),
+                });
+                if let Some(options) = subscription.options() {
+                    command["options"] = options;
+                }
+                command
+            }
+            Command::Unsubscribe(topic) => json!({
+                "action": "unsubscribe",
+                "topic": topic,
+            }),
+            Command::UpdateAccounts { add, remove } => json!({
+                "action": "update",
+                "topic": Topic::Confirmation,
+                "options": {
+                    "accounts_add": add,
+                    "accounts_del": remove,
+                },
+            }),
+        }
+    }
+}
+
+/// Handle to change the subscriptions of a [WebSocketClient] or a [WebSocketSession] while
+/// its messages are consumed
+#[derive(Debug, Clone)]
+pub struct WebSocketHandle {
+    commands: mpsc::UnboundedSender<Command>,
+}
+
+impl WebSocketHandle {
+    fn channel() -> (Self, mpsc::UnboundedReceiver<Command>) {
+        let (commands, receiver) = mpsc::unbounded();
+        (WebSocketHandle { commands }, receiver)
+    }
+
+    fn send(&self, command: Command) -> Result<(), Error> {
+        self.commands.unbounded_send(command).map_err(|_| Error::WebSocketClosed)
+    }
+
+    /// Subscribe to a topic, replacing the options of an existing subscription to it
+    pub fn subscribe(&self, subscription: Subscription) -> Result<(), Error> {
+        self.send(Command::Subscribe(subscription))
+    }
+
+    pub fn unsubscribe(&self, topic: Topic) -> Result<(), Error> {
+        self.send(Command::Unsubscribe(topic))
+    }
+
+    /// Watch `add` and stop watching `remove` on the existing `confirmation` subscription
+    pub fn update_confirmation_accounts(&self, add: &[Address], remove: &[Address]) -> Result<(), Error> {
+        self.send(Command::UpdateAccounts {
+            add: add.to_vec(),
+            remove: remove.to_vec(),
+        })
+    }
+}
+
+/// Keep only the confirmations of a stream of messages
+fn confirmations(messages: impl Stream<Item = Result<Message, Error>>) -> impl Stream<Item = Result<Confirmation, Error>> {
+    messages.filter_map(|message| async move {
+        match message {
+            Ok(Message::Confirmation(confirmation)) => Some(Ok(confirmation)),
+            Ok(_) => None,
+            Err(error) => Some(Err(error)),
+        }
+    })
+}
+
+/// Connection to the websocket of a node
+///
+/// Subscriptions requested through [handle](WebSocketClient::handle) are sent while the stream
+/// returned by [messages](WebSocketClient::messages) is polled. The stream ends when the
+/// connection is lost, see [WebSocketSession] to reconnect automatically.
+pub struct WebSocketClient {
+    socket: Socket,
+    commands: mpsc::UnboundedReceiver<Command>,
+    handle: WebSocketHandle,
+}
+
+impl WebSocketClient {
+    /// Connect to the websocket at `url`, like `ws://localhost:7074`
+    pub async fn connect(url: &str) -> Result<Self, Error> {
+        let (socket, _) = connect_async(url).await?;
+        let (handle, commands) = WebSocketHandle::channel();
+        Ok(WebSocketClient {
+            socket,
+            commands,
+            handle,
+        })
+    }
+
+    pub fn handle(&self) -> WebSocketHandle {
+        self.handle.clone()
+    }
+
+    /// See [WebSocketHandle::subscribe]
+    pub fn subscribe(&self, subscription: Subscription) -> Result<(), Error> {
+        self.handle.subscribe(subscription)
+    }
+
+    /// Notifications of the subscribed topics, until the node closes the connection
+    pub fn messages(self) -> impl Stream<Item = Result<Message, Error>> {
+        let WebSocketClient { socket, commands, handle } = self;
+        // the stream owns a handle so that commands are not closed before the socket
+        stream::unfold((socket, commands, handle), |(mut socket, mut commands, handle)| async move {
+            loop {
+                let next = match future::select(commands.next(), socket.next()).await {
+                    Either::Left((Some(command), _)) => {
+                        match socket.send(tungstenite::Message::Text(command.to_json().to_string())).await {
+                            Ok(()) => continue,
+                            Err(error) => Err(error.into()),
+                        }
+                    }
+                    Either::Left((None, _)) => continue,
+                    Either::Right((Some(Ok(tungstenite::Message::Text(text))), _)) => match Message::parse(&text) {
+                        Ok(Some(message)) => Ok(message),
+                        Ok(None) => continue,
+                        Err(error) => Err(error),
+                    },
+                    Either::Right((Some(Ok(tungstenite::Message::Close(_))), _)) | Either::Right((None, _)) => return None,
+                    Either::Right((Some(Ok(_)), _)) => continue,
+                    Either::Right((Some(Err(error)), _)) => Err(error.into()),
+                };
+                return Some((next, (socket, commands, handle)));
+            }
+        })
+    }
+
+    /// Blocks confirmed on the `confirmation` topic
+    pub fn confirmations(self) -> impl Stream<Item = Result<Confirmation, Error>> {
+        confirmations(self.messages())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Raw;
+    use tokio::net::TcpListener;
+    use tokio_tungstenite::accept_async;
+
+    #[test]
+    fn parses_topics() {
+        let parse = |topic: &str, message: Value| Message::parse(&json!({
+            "topic": topic,
+            "time": "1624270400000",
+            "message": message,
+        }).to_string()).unwrap().unwrap();
+
+        let stopped = parse("stopped_election", json!({"hash": "40DB7EC1F71F7B3B66982007F20E687148BDB875E533121259C0BF69AEFE88D3"}));
+        assert!(matches!(stopped, Message::StoppedElection(_)));
+        let difficulty = parse("active_difficulty", json!({
+            "multiplier": "1.5",
+            "network_current": "fffffe0000000000",
+            "network_minimum": "fffffe0000000000",
+            "network_receive_current": "fffffe0000000000",
+            "network_receive_minimum": "fffffe0000000000",
+        }));
+        assert!(matches!(difficulty, Message::ActiveDifficulty(difficulty) if difficulty.multiplier == 1.5));
+        let block = parse("new_unconfirmed_block", json!({
+            "type": "state",
+            "account": "ban_1hgtqu7cmgxb66ta4gxt7coimqcxp86nzi5b7u14ip9zzpqr16a3dbqdja1f",
+            "previous": "40DB7EC1F71F7B3B66982007F20E687148BDB875E533121259C0BF69AEFE88D3",
+            "representative": "ban_1fomoz167m7o38gw4rzt7hz67oq6itejpt4yocrfywujbpatd711cjew8gjj",
+            "balance": "0",
+            "link": "0000000000000000000000000000000000000000000000000000000000000000",
+            "link_as_account": "ban_1111111111111111111111111111111111111111111111111111hifc8npp",
+            "signature": "5B11B17DB9C8FE0CC58CAC6A6EECEF9CB122DA8A81C6D3DB1B5EE3AB065AA8F8CB1D6765C8EB91B58530C5FF5987AD95E6D34BB57F44257E20795EE412E61600",
+            "work": "2BF29EF00786A6BC",
+            "subtype": "change",
+        }));
+        assert!(matches!(block, Message::NewUnconfirmedBlock(block) if block.subtype == crate::types::BlockSubtype::Change));
+
+        assert!(Message::parse(r#"{"ack": "subscribe", "time": "1624270400000"}"#).unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn streams_confirmations() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let node = tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut socket = accept_async(socket).await.unwrap();
+            let mut requests = Vec::new();
+            for _ in 0..2 {
+                let request = socket.next().await.unwrap().unwrap().into_text().unwrap();
+                requests.push(serde_json::from_str::<Value>(&request).unwrap());
+            }
+            let confirmation = json!({
+                "topic": "confirmation",
+                "time": "1624270400000",
+                "message": {
+                    "account": "ban_1hgtqu7cmgxb66ta4gxt7coimqcxp86nzi5b7u14ip9zzpqr16a3dbqdja1f",
+                    "amount": "100",
+                    "hash": "40DB7EC1F71F7B3B66982007F20E687148BDB875E533121259C0BF69AEFE88D3",
+                    "confirmation_type": "active_quorum",
+                },
+            });
+            socket.send(tungstenite::Message::Text(json!({"ack": "subscribe", "time": "1624270400000"}).to_string())).await.unwrap();
+            socket.send(tungstenite::Message::Text(confirmation.to_string())).await.unwrap();
+            socket.close(None).await.unwrap();
+            requests
+        });
+
+        let address = Address("ban_1hgtqu7cmgxb66ta4gxt7coimqcxp86nzi5b7u14ip9zzpqr16a3dbqdja1f".into());
+        let watched = vec![address.clone()];
+        let client = WebSocketClient::connect(&url).await.unwrap();
+        client.subscribe(Subscription::Confirmation(ConfirmationOptions::accounts(watched.clone()))).unwrap();
+        client.handle().update_confirmation_accounts(&[], &watched).unwrap();
+        let confirmations: Vec<_> = client.confirmations().collect().await;
+        assert_eq!(confirmations.len(), 1);
+        assert_eq!(confirmations[0].as_ref().unwrap().amount, Raw::new(100u32));
+
+        let requests = node.await.unwrap();
+        assert_eq!(requests[0]["action"], "subscribe");
+        assert_eq!(requests[0]["options"]["accounts"], json!([address]));
+        assert_eq!(requests[0]["options"]["include_block"], "true");
+        assert_eq!(requests[1]["action"], "update");
+        assert_eq!(requests[1]["options"]["accounts_del"], json!([address]));
+    }
+}
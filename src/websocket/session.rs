@@ -0,0 +1,799 @@
+use crate::{Address, BananoApi, Error, Raw, api::{AccountHistoryOptions, BlockContents, HistoryEntry, ReceivableOptions, RetryPolicy}, types::{BlockHash, BlockSubtype, StateBlock}};
+use super::{BlockWithSubtype, Command, Confirmation, ConfirmationType, Message, Socket, Subscription, Topic, WebSocketHandle, confirmations};
+use futures::{channel::mpsc, stream, SinkExt, Stream, StreamExt};
+use log::{debug, warn};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::{collections::{HashMap, HashSet, VecDeque}, time::Duration};
+use tokio::time::{sleep, sleep_until, Instant};
+use tokio_tungstenite::{connect_async, tungstenite};
+
+/// Confirmations remembered per watched account to skip duplicates
+const SEEN_CAPACITY: usize = 1024;
+/// Blocks requested at once when backfilling the history of an account
+const HISTORY_PAGE: u64 = 100;
+
+/// Options of a [WebSocketSession]
+#[derive(Debug, Clone)]
+pub struct SessionOptions {
+    /// Delays between connection attempts. The session gives up after `max_retries` consecutive
+    /// failures, which never happens by default.
+    pub reconnect: RetryPolicy,
+    /// Send a ping after this long without hearing from the node
+    pub keepalive: Duration,
+    /// Reconnect when the node does not answer a ping or a subscription within this delay
+    pub ack_timeout: Duration,
+}
+
+impl Default for SessionOptions {
+    fn default() -> Self {
+        SessionOptions {
+            reconnect: RetryPolicy {
+                max_retries: u32::MAX,
+                initial_backoff: Duration::from_millis(500),
+                max_backoff: Duration::from_secs(30),
+                jitter: true,
+            },
+            keepalive: Duration::from_secs(30),
+            ack_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Connection to the websocket of a node which survives disconnections
+///
+/// The session remembers its subscriptions, reconnects with backoff when the connection drops or
+/// stops answering pings, and subscribes again. With [with_backfill](WebSocketSession::with_backfill),
+/// blocks of the watched accounts confirmed while disconnected are recovered from their history,
+/// as confirmations of type [Backfill](ConfirmationType::Backfill). Sends to a watched account
+/// which are not received yet are recovered with `receivable`.
+///
+/// # Example:
+/// ```no_run
+/// use banano_rs::{Address, BananoApi, websocket::{ConfirmationOptions, Subscription, WebSocketSession}};
+/// use futures::StreamExt;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), banano_rs::Error> {
+///     let address = Address("ban_1hgtqu7cmgxb66ta4gxt7coimqcxp86nzi5b7u14ip9zzpqr16a3dbqdja1f".into());
+///     let session = WebSocketSession::new("ws://localhost:7074")
+///         .with_backfill(BananoApi::new("http://localhost:7072".into()));
+///     session.subscribe(Subscription::Confirmation(ConfirmationOptions::accounts(vec![address])))?;
+///
+///     let mut confirmations = Box::pin(session.confirmations());
+///     while let Some(confirmation) = confirmations.next().await {
+///         println!("{} confirmed", confirmation?.hash);
+///     }
+///     Ok(())
+/// }
+/// ```
+pub struct WebSocketSession {
+    url: String,
+    options: SessionOptions,
+    api: Option<BananoApi>,
+    commands: mpsc::UnboundedReceiver<Command>,
+    handle: WebSocketHandle,
+}
+
+impl WebSocketSession {
+    /// Session with the websocket at `url`, which connects once its messages are polled
+    pub fn new(url: &str) -> Self {
+        let (handle, commands) = WebSocketHandle::channel();
+        WebSocketSession {
+            url: url.into(),
+            options: SessionOptions::default(),
+            api: None,
+            commands,
+            handle,
+        }
+    }
+
+    pub fn with_options(mut self, options: SessionOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Recover confirmations missed while disconnected using the RPC of `api`
+    pub fn with_backfill(mut self, api: BananoApi) -> Self {
+        self.api = Some(api);
+        self
+    }
+
+    pub fn handle(&self) -> WebSocketHandle {
+        self.handle.clone()
+    }
+
+    /// See [WebSocketHandle::subscribe]
+    pub fn subscribe(&self, subscription: Subscription) -> Result<(), Error> {
+        self.handle.subscribe(subscription)
+    }
+
+    /// Notifications of the subscribed topics. The stream only ends, with an error, when
+    /// reconnection attempts are exhausted.
+    pub fn messages(self) -> impl Stream<Item = Result<Message, Error>> {
+        let driver = Driver {
+            url: self.url,
+            options: self.options,
+            api: self.api,
+            commands: self.commands,
+            _handle: self.handle,
+            subscriptions: HashMap::new(),
+            socket: None,
+            failures: 0,
+            connected: false,
+            closed: false,
+            backlog: VecDeque::new(),
+            watermarks: HashMap::new(),
+            acks: HashMap::new(),
+            next_id: 0,
+            last_activity: Instant::now(),
+            ping_sent: None,
+        };
+        stream::unfold(driver, |mut driver| async move {
+            driver.next().await.map(|message| (message, driver))
+        })
+    }
+
+    /// Blocks confirmed on the `confirmation` topic
+    pub fn confirmations(self) -> impl Stream<Item = Result<Confirmation, Error>> {
+        confirmations(self.messages())
+    }
+}
+
+/// Confirmation height of a watched account, and the blocks above it already sent
+#[derive(Default)]
+struct Watermark {
+    height: u64,
+    /// Block at `height`, when known
+    frontier: Option<BlockHash>,
+    seen: HashSet<BlockHash>,
+    order: VecDeque<BlockHash>,
+}
+
+impl Watermark {
+    /// Raise the height past a confirmed block of the account following the one at the current height,
+    /// so that it is not backfilled again once forgotten
+    fn confirm(&mut self, confirmation: &Confirmation) {
+        let previous = match &confirmation.contents {
+            Some(contents) => &contents.block.previous,
+            None => return,
+        };
+        let follows = match &self.frontier {
+            Some(frontier) => frontier == previous,
+            None => self.height == 0 && *previous == BlockHash::zero(),
+        };
+        if follows {
+            self.height += 1;
+            self.frontier = Some(confirmation.hash.clone());
+        }
+    }
+
+    /// Remember `hash`, returns whether it was new
+    fn insert(&mut self, hash: &BlockHash) -> bool {
+        if !self.seen.insert(hash.clone()) {
+            return false;
+        }
+        self.order.push_back(hash.clone());
+        if self.order.len() > SEEN_CAPACITY {
+            if let Some(oldest) = self.order.pop_front() {
+                self.seen.remove(&oldest);
+            }
+        }
+        true
+    }
+}
+
+#[derive(Deserialize)]
+struct Ack {
+    ack: String,
+    id: Option<String>,
+}
+
+enum Event {
+    Command(Option<Command>),
+    Frame(Option<Result<tungstenite::Message, tungstenite::Error>>),
+    Timer,
+}
+
+struct Driver {
+    url: String,
+    options: SessionOptions,
+    api: Option<BananoApi>,
+    commands: mpsc::UnboundedReceiver<Command>,
+    // keeps commands open for as long as the stream lives
+    _handle: WebSocketHandle,
+    subscriptions: HashMap<Topic, Subscription>,
+    socket: Option<Socket>,
+    /// Consecutive connection failures
+    failures: u32,
+    /// Whether a connection was ever established, to backfill after the following ones
+    connected: bool,
+    closed: bool,
+    backlog: VecDeque<Message>,
+    watermarks: HashMap<Address, Watermark>,
+    /// Deadlines of the commands waiting for an ack, by id
+    acks: HashMap<String, Instant>,
+    next_id: u64,
+    last_activity: Instant,
+    ping_sent: Option<Instant>,
+}
+
+impl Driver {
+    async fn next(&mut self) -> Option<Result<Message, Error>> {
+        loop {
+            if let Some(message) = self.backlog.pop_front() {
+                return Some(Ok(message));
+            }
+            if self.closed {
+                return None;
+            }
+            if self.socket.is_none() {
+                if let Err(error) = self.connect().await {
+                    self.closed = true;
+                    return Some(Err(error));
+                }
+                continue;
+            }
+
+            let deadline = self.deadline();
+            let event = {
+                let socket = self.socket.as_mut().unwrap();
+                tokio::select! {
+                    command = self.commands.next() => Event::Command(command),
+                    frame = socket.next() => Event::Frame(frame),
+                    _ = sleep_until(deadline) => Event::Timer,
+                }
+            };
+            match event {
+                Event::Command(Some(command)) => self.apply(command).await,
+                Event::Command(None) => {}
+                Event::Frame(Some(Ok(tungstenite::Message::Text(text)))) => {
+                    self.alive();
+                    if let Some(message) = self.receive(&text) {
+                        return Some(Ok(message));
+                    }
+                }
+                Event::Frame(Some(Ok(tungstenite::Message::Close(_)))) | Event::Frame(None) => self.disconnect("connection closed"),
+                Event::Frame(Some(Ok(_))) => self.alive(),
+                Event::Frame(Some(Err(error))) => self.disconnect(&error.to_string()),
+                Event::Timer => self.tick().await,
+            }
+        }
+    }
+
+    /// Connect, subscribe again and backfill, retrying with backoff
+    async fn connect(&mut self) -> Result<(), Error> {
+        loop {
+            if self.failures > 0 {
+                sleep(self.options.reconnect.backoff(self.failures - 1)).await;
+            }
+            match connect_async(self.url.as_str()).await {
+                Ok((socket, _)) => {
+                    self.socket = Some(socket);
+                    break;
+                }
+                Err(error) if self.failures < self.options.reconnect.max_retries => {
+                    warn!("Websocket connection to {} failed: {}", self.url, error);
+                    self.failures += 1;
+                }
+                Err(error) => return Err(error.into()),
+            }
+        }
+        debug!("Connected to websocket {}", self.url);
+        self.acks.clear();
+        self.last_activity = Instant::now();
+        self.ping_sent = None;
+
+        let subscriptions: Vec<Subscription> = self.subscriptions.values().cloned().collect();
+        for subscription in subscriptions {
+            self.send(Command::Subscribe(subscription).to_json(), true).await;
+        }
+        if self.connected {
+            self.backfill().await;
+        }
+        self.connected = true;
+        Ok(())
+    }
+
+    fn disconnect(&mut self, reason: &str) {
+        warn!("Websocket {} disconnected: {}", self.url, reason);
+        self.socket = None;
+        self.failures += 1;
+    }
+
+    /// Some frame came from the node, which is thus alive
+    fn alive(&mut self) {
+        self.failures = 0;
+        self.last_activity = Instant::now();
+        self.ping_sent = None;
+    }
+
+    /// When the next ping is due or the oldest ack times out
+    fn deadline(&self) -> Instant {
+        let ping = match self.ping_sent {
+            Some(sent) => sent + self.options.ack_timeout,
+            None => self.last_activity + self.options.keepalive,
+        };
+        self.acks.values().copied().fold(ping, Instant::min)
+    }
+
+    async fn tick(&mut self) {
+        let now = Instant::now();
+        if self.acks.values().any(|deadline| *deadline <= now) {
+            self.disconnect("subscription not acknowledged");
+        } else if self.ping_sent.is_some_and(|sent| sent + self.options.ack_timeout <= now) {
+            self.disconnect("ping not answered");
+        } else if self.last_activity + self.options.keepalive <= now {
+            self.send(json!({ "action": "ping" }), false).await;
+            self.ping_sent = Some(now);
+        }
+    }
+
+    /// Send `command`, expecting an ack if `ack` is set. Failures are handled by reconnecting.
+    async fn send(&mut self, mut command: Value, ack: bool) {
+        if ack {
+            self.next_id += 1;
+            let id = self.next_id.to_string();
+            command["ack"] = json!(true);
+            command["id"] = json!(id);
+            self.acks.insert(id, Instant::now() + self.options.ack_timeout);
+        }
+        if let Some(socket) = self.socket.as_mut() {
+            if let Err(error) = socket.send(tungstenite::Message::Text(command.to_string())).await {
+                self.disconnect(&error.to_string());
+            }
+        }
+    }
+
+    /// Update the desired subscriptions, then forward `command` to the node
+    async fn apply(&mut self, command: Command) {
+        match &command {
+            Command::Subscribe(subscription) => {
+                if let Subscription::Confirmation(options) = subscription {
+                    self.watermarks.retain(|account, _| options.accounts.contains(account));
+                    self.watch(&options.accounts).await;
+                }
+                self.subscriptions.insert(subscription.topic(), subscription.clone());
+            }
+            Command::Unsubscribe(topic) => {
+                if *topic == Topic::Confirmation {
+                    self.watermarks.clear();
+                }
+                self.subscriptions.remove(topic);
+            }
+            Command::UpdateAccounts { add, remove } => {
+                if let Some(Subscription::Confirmation(options)) = self.subscriptions.get_mut(&Topic::Confirmation) {
+                    options.accounts.retain(|account| !remove.contains(account));
+                    options.accounts.extend(add.iter().filter(|account| !options.accounts.contains(account)).cloned().collect::<Vec<_>>());
+                }
+                for account in remove {
+                    self.watermarks.remove(account);
+                }
+                self.watch(add).await;
+            }
+        }
+        self.send(command.to_json(), true).await;
+    }
+
+    /// Handle a text frame, returning it unless it is an ack or an already sent confirmation
+    fn receive(&mut self, text: &str) -> Option<Message> {
+        if let Ok(ack) = serde_json::from_str::<Ack>(text) {
+            debug!("Websocket ack {} {:?}", ack.ack, ack.id);
+            if let Some(id) = ack.id {
+                self.acks.remove(&id);
+            }
+            return None;
+        }
+        match Message::parse(text) {
+            Ok(Some(Message::Confirmation(confirmation))) => {
+                let mut new = match self.watermarks.get_mut(&confirmation.account) {
+                    Some(watermark) => {
+                        watermark.confirm(&confirmation);
+                        watermark.insert(&confirmation.hash)
+                    }
+                    None => true,
+                };
+                // sends to a watched account may have been backfilled from its receivable blocks
+                if let Some(watermark) = confirmation.destination()
+                    .filter(|destination| *destination != confirmation.account)
+                    .and_then(|destination| self.watermarks.get_mut(&destination))
+                {
+                    new &= watermark.insert(&confirmation.hash);
+                }
+                if new {
+                    Some(Message::Confirmation(confirmation))
+                } else {
+                    debug!("Skipping confirmation of {} already sent", confirmation.hash);
+                    None
+                }
+            }
+            Ok(message) => message,
+            Err(error) => {
+                warn!("Invalid websocket message {}: {}", text, error);
+                None
+            }
+        }
+    }
+
+    /// Current confirmation height of `account` and the block at that height, zero if it is not opened
+    async fn confirmation_height(api: &BananoApi, account: &Address) -> Result<(u64, Option<BlockHash>), Error> {
+        match api.account_info(account).await {
+            Ok(info) => {
+                let frontier = info.confirmation_height_frontier.and_then(|frontier| frontier.parse().ok());
+                Ok((info.confirmation_height as u64, frontier))
+            }
+            Err(Error::NodeError(error)) if error == "Account not found" => Ok((0, None)),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Record the confirmation height of the accounts not watched yet, backfilling from there
+    async fn watch(&mut self, accounts: &[Address]) {
+        let api = match &self.api {
+            Some(api) => api,
+            None => return,
+        };
+        for account in accounts {
+            if self.watermarks.contains_key(account) {
+                continue;
+            }
+            match Self::confirmation_height(api, account).await {
+                Ok((height, frontier)) => {
+                    self.watermarks.insert(account.clone(), Watermark { height, frontier, ..Default::default() });
+                }
+                Err(error) => warn!("Cannot backfill {}, confirmation height unknown: {}", account.0, error),
+            }
+        }
+    }
+
+    /// Queue the blocks of watched accounts confirmed since their watermark, and the sends to them
+    /// not received yet
+    async fn backfill(&mut self) {
+        let api = match &self.api {
+            Some(api) => api.clone(),
+            None => return,
+        };
+        let accounts = match self.subscriptions.get(&Topic::Confirmation) {
+            Some(Subscription::Confirmation(options)) => options.accounts.clone(),
+            _ => return,
+        };
+        self.watch(&accounts).await;
+
+        for (account, watermark) in self.watermarks.iter_mut() {
+            let (confirmed, missed) = match Self::backfill_account(&api, account, watermark.height).await {
+                Ok(backfill) => backfill,
+                Err(error) => {
+                    warn!("Cannot backfill {}: {}", account.0, error);
+                    continue;
+                }
+            };
+            if confirmed > watermark.height {
+                // the newest missed block is the one at the confirmation height
+                watermark.height = confirmed;
+                watermark.frontier = missed.last().map(|confirmation| confirmation.hash.clone());
+            }
+            for confirmation in missed {
+                if watermark.insert(&confirmation.hash) {
+                    self.backlog.push_back(Message::Confirmation(confirmation));
+                }
+            }
+
+            match Self::backfill_receivable(&api, account).await {
+                Ok(sends) => {
+                    for confirmation in sends {
+                        if watermark.insert(&confirmation.hash) {
+                            self.backlog.push_back(Message::Confirmation(confirmation));
+                        }
+                    }
+                }
+                Err(error) => warn!("Cannot backfill sends to {}: {}", account.0, error),
+            }
+        }
+    }
+
+    /// Confirmed sends to `account` which it did not receive yet
+    async fn backfill_receivable(api: &BananoApi, account: &Address) -> Result<Vec<Confirmation>, Error> {
+        let receivable = api.receivable(account, ReceivableOptions::default()).await?;
+        if receivable.is_empty() {
+            return Ok(Vec::new());
+        }
+        let hashes: Vec<BlockHash> = receivable.keys().cloned().collect();
+        let mut blocks = api.blocks_info(&hashes).await?;
+        let sends: Vec<Confirmation> = receivable
+            .into_iter()
+            .filter_map(|(hash, receivable)| {
+                let info = blocks.remove(&hash)?;
+                let contents = match info.contents {
+                    BlockContents::State(block) => Some(BlockWithSubtype {
+                        block,
+                        subtype: info.subtype.unwrap_or(BlockSubtype::Send),
+                    }),
+                    BlockContents::Legacy(_) => None,
+                };
+                Some(Confirmation {
+                    account: info.block_account,
+                    amount: receivable.amount,
+                    hash,
+                    confirmation_type: ConfirmationType::Backfill,
+                    contents,
+                    election_info: None,
+                })
+            })
+            .collect();
+        debug!("Backfilled {} receivable sends to {}", sends.len(), account.0);
+        Ok(sends)
+    }
+
+    /// Confirmation height of `account` and its blocks confirmed above `height`, oldest first
+    async fn backfill_account(api: &BananoApi, account: &Address, height: u64) -> Result<(u64, Vec<Confirmation>), Error> {
+        let info = match api.account_info(account).await {
+            Ok(info) => info,
+            Err(Error::NodeError(error)) if error == "Account not found" => return Ok((height, Vec::new())),
+            Err(error) => return Err(error),
+        };
+        let confirmed = info.confirmation_height as u64;
+        if confirmed <= height {
+            return Ok((height, Vec::new()));
+        }
+        // walk the chain backwards from the confirmation height, one page at a time
+        let mut options = AccountHistoryOptions {
+            raw: true,
+            offset: Some(info.block_count as u64 - confirmed),
+            ..Default::default()
+        };
+        let mut missed = Vec::new();
+        loop {
+            let count = (confirmed - height).saturating_sub(missed.len() as u64).clamp(1, HISTORY_PAGE);
+            let page = api.account_history(account, count, &options).await?;
+            let reached = page.history.is_empty() || page.history.iter().any(|entry| entry.height <= height + 1);
+            missed.extend(page.history
+                .into_iter()
+                .filter(|entry| entry.height > height && entry.height <= confirmed)
+                .map(|entry| backfilled(account, entry)));
+            match page.previous {
+                Some(previous) if !reached => {
+                    options.head = Some(previous);
+                    options.offset = None;
+                }
+                _ => break,
+            }
+        }
+        missed.reverse();
+        debug!("Backfilled {} confirmations of {}", missed.len(), account.0);
+        Ok((confirmed, missed))
+    }
+}
+
+/// Confirmation of a block of `account` found in its history
+fn backfilled(account: &Address, entry: HistoryEntry) -> Confirmation {
+    let contents = match (entry.subtype, entry.representative, entry.balance, entry.previous, entry.link) {
+        (Some(subtype), Some(representative), Some(balance), Some(previous), Some(link)) => Some(BlockWithSubtype {
+            block: StateBlock {
+                account: account.clone(),
+                previous,
+                representative,
+                balance,
+                link,
+                signature: None,
+                work: None,
+            },
+            subtype,
+        }),
+        _ => None,
+    };
+    Confirmation {
+        account: account.clone(),
+        amount: entry.amount.unwrap_or_else(Raw::zero),
+        hash: entry.hash,
+        confirmation_type: ConfirmationType::Backfill,
+        contents,
+        election_info: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{api::MockNode, types::{PrivateKey, PublicKey, Seed}};
+    use super::super::ConfirmationOptions;
+    use tokio::{net::TcpListener, sync::oneshot};
+    use tokio_tungstenite::accept_async;
+
+    /// Accept a connection and acknowledge its subscription
+    async fn accept(listener: &TcpListener) -> (Socket, Value) {
+        let (socket, _) = listener.accept().await.unwrap();
+        let mut socket: Socket = accept_async(tokio_tungstenite::MaybeTlsStream::Plain(socket)).await.unwrap();
+        let subscribe: Value = serde_json::from_str(&socket.next().await.unwrap().unwrap().into_text().unwrap()).unwrap();
+        let ack = json!({"ack": "subscribe", "time": "1624270400000", "id": subscribe["id"]});
+        socket.send(tungstenite::Message::Text(ack.to_string())).await.unwrap();
+        (socket, subscribe)
+    }
+
+    fn confirmation(account: &Address, hash: &BlockHash) -> tungstenite::Message {
+        tungstenite::Message::Text(json!({
+            "topic": "confirmation",
+            "time": "1624270400000",
+            "message": {
+                "account": account,
+                "amount": "1",
+                "hash": hash,
+                "confirmation_type": "active_quorum",
+            },
+        }).to_string())
+    }
+
+    #[tokio::test]
+    async fn reconnects_and_backfills() {
+        let node = MockNode::empty();
+        let key = PrivateKey::from_seed(Seed([7; 32]), 0);
+        let account = Address::from(PublicKey::from(&key));
+        let source = Address(MockNode::ACCOUNT.into());
+        let open = node.add_account(&account, Raw::new(10u32), &account).unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let (disconnect, disconnected) = oneshot::channel();
+        let (watched, opened) = (account.clone(), open.clone());
+        let server = tokio::spawn(async move {
+            let (mut socket, first) = accept(&listener).await;
+            socket.send(confirmation(&watched, &opened)).await.unwrap();
+            disconnected.await.unwrap();
+            drop(socket);
+
+            let (mut socket, second) = accept(&listener).await;
+            (first, second, socket.next().await)
+        });
+
+        let session = WebSocketSession::new(&url)
+            .with_options(SessionOptions {
+                reconnect: RetryPolicy {
+                    initial_backoff: Duration::from_millis(10),
+                    ..SessionOptions::default().reconnect
+                },
+                ..Default::default()
+            })
+            .with_backfill(node.api());
+        session.subscribe(Subscription::Confirmation(ConfirmationOptions::accounts(vec![account.clone()]))).unwrap();
+        let mut confirmations = Box::pin(session.confirmations());
+        let live = confirmations.next().await.unwrap().unwrap();
+        assert_eq!(live.confirmation_type, ConfirmationType::ActiveQuorum);
+
+        // received while disconnected
+        let send = node.add_receivable(&account, &source, Raw::new(5u32)).unwrap();
+        let mut receive = StateBlock {
+            account: account.clone(),
+            previous: open,
+            representative: account.clone(),
+            balance: Raw::new(15u32),
+            link: send.into(),
+            signature: None,
+            work: None,
+        };
+        receive.sign(&key).unwrap();
+        node.api().process(&receive, Default::default()).await.unwrap();
+        // sent while disconnected, not received yet
+        let unreceived = node.add_receivable(&account, &source, Raw::new(7u32)).unwrap();
+        disconnect.send(()).unwrap();
+
+        let missed = confirmations.next().await.unwrap().unwrap();
+        assert_eq!(missed.confirmation_type, ConfirmationType::Backfill);
+        assert_eq!(missed.hash, receive.hash().unwrap());
+        assert_eq!(missed.amount, Raw::new(5u32));
+        assert_eq!(missed.subtype(), Some(crate::types::BlockSubtype::Receive));
+        let missed = confirmations.next().await.unwrap().unwrap();
+        assert_eq!(missed.confirmation_type, ConfirmationType::Backfill);
+        assert_eq!(missed.hash, unreceived);
+        assert_eq!(missed.account, source);
+        assert_eq!(missed.amount, Raw::new(7u32));
+        assert_eq!(missed.destination(), Some(account.clone()));
+
+        drop(confirmations);
+        let (first, second, _) = server.await.unwrap();
+        assert_eq!(first["ack"], true);
+        assert_eq!(second["topic"], "confirmation");
+        assert_eq!(second["options"]["accounts"], json!([account]));
+    }
+
+    #[tokio::test]
+    async fn remembers_live_confirmations_across_reconnections() {
+        let node = MockNode::empty();
+        let key = PrivateKey::from_seed(Seed([8; 32]), 0);
+        let account = Address::from(PublicKey::from(&key));
+        let open = node.add_account(&account, Raw::new(10u32), &account).unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let (publish, published) = oneshot::channel::<Vec<tungstenite::Message>>();
+        let (disconnect, disconnected) = oneshot::channel();
+        let opened = open.clone();
+        let watched = account.clone();
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = accept(&listener).await;
+            socket.send(confirmation(&watched, &opened)).await.unwrap();
+            for message in published.await.unwrap() {
+                socket.send(message).await.unwrap();
+            }
+            disconnected.await.unwrap();
+            drop(socket);
+
+            let (mut socket, _) = accept(&listener).await;
+            socket.next().await
+        });
+
+        let session = WebSocketSession::new(&url)
+            .with_options(SessionOptions {
+                reconnect: RetryPolicy {
+                    initial_backoff: Duration::from_millis(10),
+                    ..SessionOptions::default().reconnect
+                },
+                ..Default::default()
+            })
+            .with_backfill(node.api());
+        session.subscribe(Subscription::Confirmation(ConfirmationOptions::accounts(vec![account.clone()]))).unwrap();
+        let mut confirmations = Box::pin(session.confirmations());
+        assert_eq!(confirmations.next().await.unwrap().unwrap().hash, open);
+
+        // more blocks than remembered, confirmed live
+        let mut previous = open;
+        let mut messages = Vec::new();
+        for _ in 0..SEEN_CAPACITY + 100 {
+            let mut block = StateBlock {
+                account: account.clone(),
+                previous: previous.clone(),
+                representative: Address(MockNode::REPRESENTATIVE.into()),
+                balance: Raw::new(10u32),
+                link: BlockHash::zero().into(),
+                signature: None,
+                work: None,
+            };
+            block.sign(&key).unwrap();
+            previous = node.api().process(&block, Default::default()).await.unwrap();
+            let mut contents = json!(block);
+            contents["subtype"] = json!("change");
+            let mut message: Value = serde_json::from_str(confirmation(&account, &previous).to_text().unwrap()).unwrap();
+            message["message"]["block"] = contents;
+            messages.push(tungstenite::Message::Text(message.to_string()));
+        }
+        let live: Vec<BlockHash> = messages.iter()
+            .map(|message| serde_json::from_str::<Value>(message.to_text().unwrap()).unwrap())
+            .map(|message| serde_json::from_value(message["message"]["hash"].clone()).unwrap())
+            .collect();
+        publish.send(messages).unwrap();
+        for hash in &live {
+            assert_eq!(&confirmations.next().await.unwrap().unwrap().hash, hash);
+        }
+
+        // confirmed while disconnected, more than a page of history
+        let mut missed = Vec::new();
+        for _ in 0..HISTORY_PAGE + 50 {
+            let mut block = StateBlock {
+                account: account.clone(),
+                previous: previous.clone(),
+                representative: account.clone(),
+                balance: Raw::new(10u32),
+                link: BlockHash::zero().into(),
+                signature: None,
+                work: None,
+            };
+            block.sign(&key).unwrap();
+            previous = node.api().process(&block, Default::default()).await.unwrap();
+            missed.push(previous.clone());
+        }
+        disconnect.send(()).unwrap();
+
+        // only the missed blocks are backfilled, oldest first
+        for hash in &missed {
+            let backfilled = confirmations.next().await.unwrap().unwrap();
+            assert_eq!(backfilled.confirmation_type, ConfirmationType::Backfill);
+            assert_eq!(&backfilled.hash, hash);
+        }
+        let pages = node.requests_for("account_history");
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[0]["count"], HISTORY_PAGE.to_string());
+        assert_eq!(pages[1]["count"], "50");
+
+        drop(confirmations);
+        server.await.unwrap();
+    }
+}
+
@@ -0,0 +1,88 @@
+use crate::{Address, types::{BlockHash, Signature}};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use serde_with::{serde_as, DisplayFromStr};
+
+/// Options of the `vote` topic
+///
+/// Only votes of `representatives` are sent, unless it is empty.
+///
+/// [Nano documentation](https://docs.nano.org/integration-guides/websockets/#votes)
+#[derive(Debug, Clone, Default)]
+pub struct VoteOptions {
+    pub representatives: Vec<Address>,
+    /// Also send votes already processed by the node
+    pub include_replays: bool,
+    /// Also send votes for blocks the node does not know
+    pub include_indeterminate: bool,
+}
+
+impl VoteOptions {
+    pub(crate) fn to_json(&self) -> Value {
+        let mut options = json!({
+            "include_replays": self.include_replays.to_string(),
+            "include_indeterminate": self.include_indeterminate.to_string(),
+        });
+        if !self.representatives.is_empty() {
+            options["representatives"] = json!(self.representatives);
+        }
+        options
+    }
+}
+
+/// How the node handled a vote
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VoteType {
+    Vote,
+    Replay,
+    Indeterminate,
+}
+
+/// Vote of a representative, from the `vote` topic
+#[serde_as]
+#[derive(Debug, Clone, Deserialize)]
+pub struct Vote {
+    /// Representative casting the vote
+    pub account: Address,
+    pub signature: Signature,
+    /// Only sent by nodes from before timestamped votes
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default)]
+    pub sequence: Option<u64>,
+    /// Milliseconds since the UNIX epoch, `u64::MAX` for a final vote
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default)]
+    pub timestamp: Option<u64>,
+    pub blocks: Vec<BlockHash>,
+    #[serde(rename = "type")]
+    pub kind: VoteType,
+}
+
+impl Vote {
+    /// Whether this is a final vote, which cannot be changed anymore
+    pub fn is_final(&self) -> bool {
+        self.timestamp == Some(u64::MAX)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_vote() {
+        let vote: Vote = serde_json::from_value(json!({
+            "account": "ban_1fomoz167m7o38gw4rzt7hz67oq6itejpt4yocrfywujbpatd711cjew8gjj",
+            "signature": "5B11B17DB9C8FE0CC58CAC6A6EECEF9CB122DA8A81C6D3DB1B5EE3AB065AA8F8CB1D6765C8EB91B58530C5FF5987AD95E6D34BB57F44257E20795EE412E61600",
+            "timestamp": "18446744073709551615",
+            "duration": "0",
+            "blocks": ["40DB7EC1F71F7B3B66982007F20E687148BDB875E533121259C0BF69AEFE88D3"],
+            "type": "replay"
+        })).unwrap();
+        assert_eq!(vote.kind, VoteType::Replay);
+        assert!(vote.is_final());
+        assert_eq!(vote.sequence, None);
+        assert_eq!(vote.blocks.len(), 1);
+    }
+}
@@ -0,0 +1,78 @@
+use crate::{encoding::{deserialize_bool, deserialize_empty_as_default}, types::{BlockHash, Difficulty, Work}};
+use serde::Deserialize;
+use serde_with::{serde_as, DisplayFromStr};
+
+/// Work generation requested by the node, from the `work` topic
+///
+/// [Nano documentation](https://docs.nano.org/integration-guides/websockets/#work-generation)
+#[serde_as]
+#[derive(Debug, Clone, Deserialize)]
+pub struct WorkGeneration {
+    #[serde(deserialize_with = "deserialize_bool")]
+    pub success: bool,
+    /// Why the generation failed, like `cancelled`, empty on success
+    pub reason: String,
+    /// Duration in milliseconds
+    #[serde_as(as = "DisplayFromStr")]
+    pub duration: u64,
+    pub request: WorkGenerationRequest,
+    /// Only available on success
+    pub result: Option<WorkGenerationResult>,
+    /// Work peers which returned invalid work
+    #[serde(default, deserialize_with = "deserialize_empty_as_default")]
+    pub bad_peers: Vec<String>,
+}
+
+#[serde_as]
+#[derive(Debug, Clone, Deserialize)]
+pub struct WorkGenerationRequest {
+    pub version: String,
+    /// Root the work was generated for
+    pub hash: BlockHash,
+    pub difficulty: Difficulty,
+    #[serde_as(as = "DisplayFromStr")]
+    pub multiplier: f64,
+}
+
+#[serde_as]
+#[derive(Debug, Clone, Deserialize)]
+pub struct WorkGenerationResult {
+    /// `local` or the address of the work peer which generated the work
+    pub source: String,
+    pub work: Work,
+    pub difficulty: Difficulty,
+    #[serde_as(as = "DisplayFromStr")]
+    pub multiplier: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn deserialize_work_generation() {
+        let generation: WorkGeneration = serde_json::from_value(json!({
+            "success": "true",
+            "reason": "",
+            "duration": "306",
+            "request": {
+                "version": "work_1",
+                "hash": "3ECAB00D8AA5A23FA0E3C4CD8EB2E33C4A8EE4D8A0F4B7DF8DB5C4F0C1E8C9A2",
+                "difficulty": "fffffe0000000000",
+                "multiplier": "1.0"
+            },
+            "result": {
+                "source": "192.168.1.101:7000",
+                "work": "2BF29EF00786A6BC",
+                "difficulty": "ffffff2c4a6e5d31",
+                "multiplier": "4.79"
+            },
+            "bad_peers": ""
+        })).unwrap();
+        assert!(generation.success);
+        assert_eq!(generation.request.difficulty, Difficulty::DEFAULT);
+        assert_eq!(generation.result.unwrap().source, "192.168.1.101:7000");
+        assert!(generation.bad_peers.is_empty());
+    }
+}